@@ -0,0 +1,180 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Outbound alert delivery: when an incoming message matches a watched term
+//! (see [`crate::AcarsHubDatabase::record_alert_hit`]), the database listener
+//! broadcasts an [`AlertMatch`] event that every configured sink (HTTP
+//! webhook, MQTT) consumes independently, so a slow or unreachable sink can't
+//! block ingestion or its sibling sinks.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Number of in-flight events buffered per sink before the slowest one starts
+/// dropping the oldest, unless overridden via `Input`'s `--alert-queue-size`.
+pub const DEFAULT_QUEUE_SIZE: usize = 256;
+
+/// Delay between redelivery attempts to a sink, scaled by attempt number.
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Number of delivery attempts made to a sink before an event is dropped.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// Delay before reconnecting a sink whose transport (e.g. the MQTT broker
+/// connection) dropped out from under it.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Emitted whenever a decoded message's text matches a watched alert term.
+///
+/// `message_id` is always `None`: matching runs in the database listener
+/// just before the message's batch insert, so no row id exists yet to
+/// attach.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertMatch {
+    pub term: String,
+    pub match_type: String,
+    pub message_id: Option<i32>,
+}
+
+/// Where a matched alert gets delivered. Built from `acarshub-settings`'
+/// `--alert-webhook-url` / `--alert-mqtt-broker` flags.
+#[derive(Debug, Clone)]
+pub enum AlertSinkConfig {
+    /// POSTs the event as JSON to `url`.
+    Webhook { url: String },
+    /// Publishes the event as JSON to `topic` on the broker at `host:port`.
+    Mqtt { host: String, port: u16, topic: String },
+}
+
+/// Spawns one Tokio task per entry in `configs`, each subscribing its own
+/// `broadcast::Receiver<AlertMatch>` to the returned sender. The broadcast
+/// channel's `queue_size` capacity is itself the "bounded queue that drops
+/// oldest": a sink that falls behind its peers (or is down, retrying)
+/// silently skips forward to the newest event instead of backing up
+/// ingestion or the other sinks.
+#[must_use]
+pub fn start_alert_sinks(configs: Vec<AlertSinkConfig>, queue_size: usize) -> broadcast::Sender<AlertMatch> {
+    let (tx, _rx) = broadcast::channel(queue_size.max(1));
+
+    for config in configs {
+        match config {
+            AlertSinkConfig::Webhook { url } => {
+                tokio::spawn(run_webhook_sink(url, tx.subscribe()));
+            }
+            AlertSinkConfig::Mqtt { host, port, topic } => {
+                tokio::spawn(run_mqtt_sink(host, port, topic, tx.subscribe()));
+            }
+        }
+    }
+
+    tx
+}
+
+/// Recovers from [`broadcast::error::RecvError::Lagged`] by logging and
+/// continuing; returns `false` when the channel is closed, in which case the
+/// sink's loop should exit.
+fn log_lag(sink: &str, error: broadcast::error::RecvError) -> bool {
+    match error {
+        broadcast::error::RecvError::Lagged(skipped) => {
+            warn!("Alert sink {sink} lagged, dropped {skipped} event(s)");
+            true
+        }
+        broadcast::error::RecvError::Closed => false,
+    }
+}
+
+async fn run_webhook_sink(url: String, mut rx: broadcast::Receiver<AlertMatch>) {
+    let client = reqwest::Client::new();
+
+    loop {
+        match rx.recv().await {
+            Ok(event) => deliver_webhook(&client, &url, &event).await,
+            Err(e) if log_lag(&url, e) => {}
+            Err(_) => break,
+        }
+    }
+}
+
+async fn deliver_webhook(client: &reqwest::Client, url: &str, event: &AlertMatch) {
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        match client.post(url).json(event).send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => warn!(
+                "Webhook {url} returned {} delivering alert for term {:?} (attempt {attempt}/{MAX_DELIVERY_ATTEMPTS})",
+                response.status(),
+                event.term
+            ),
+            Err(e) => warn!(
+                "Webhook {url} delivery failed: {e} (attempt {attempt}/{MAX_DELIVERY_ATTEMPTS})"
+            ),
+        }
+
+        tokio::time::sleep(RETRY_BACKOFF * attempt).await;
+    }
+
+    error!("Giving up delivering alert for term {:?} to webhook {url}", event.term);
+}
+
+async fn run_mqtt_sink(host: String, port: u16, topic: String, mut rx: broadcast::Receiver<AlertMatch>) {
+    loop {
+        let mut options = rumqttc::MqttOptions::new("acarshub", host.clone(), port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, mut eventloop) = rumqttc::AsyncClient::new(options, 10);
+
+        // Drive the eventloop in the background; publishes below only need
+        // the client handle, but rumqttc requires the eventloop be polled
+        // for the connection to make progress at all.
+        tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(e) if log_lag(&format!("{host}:{port}"), e) => continue,
+                Err(_) => return,
+            };
+
+            if !deliver_mqtt(&client, &topic, &event).await {
+                warn!("MQTT sink {host}:{port} lost its connection, reconnecting in {}s", RECONNECT_BACKOFF.as_secs());
+                break;
+            }
+        }
+
+        tokio::time::sleep(RECONNECT_BACKOFF).await;
+    }
+}
+
+async fn deliver_mqtt(client: &rumqttc::AsyncClient, topic: &str, event: &AlertMatch) -> bool {
+    let payload = match serde_json::to_vec(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize alert event for MQTT: {e}");
+            return true;
+        }
+    };
+
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        match client.publish(topic, rumqttc::QoS::AtLeastOnce, false, payload.clone()).await {
+            Ok(()) => return true,
+            Err(e) => warn!("MQTT publish to {topic} failed: {e} (attempt {attempt}/{MAX_DELIVERY_ATTEMPTS})"),
+        }
+
+        tokio::time::sleep(RETRY_BACKOFF * attempt).await;
+    }
+
+    false
+}
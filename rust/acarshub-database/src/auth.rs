@@ -0,0 +1,151 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! User accounts and issued-token bookkeeping for the webserver's auth
+//! layer. Passwords are hashed with argon2; the webserver is the only
+//! thing that knows how to mint/verify the JWTs themselves, this module
+//! just persists who's allowed to log in and which `jti`s are still live.
+
+use anyhow::{Context, Result, bail};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng};
+use argon2::Argon2;
+use diesel::prelude::*;
+
+use crate::DbPool;
+use crate::models::{ApiToken, NewApiToken, NewUser, User};
+use crate::schema::{api_tokens, users};
+
+/// Hashes `password` with argon2 and inserts a new user row.
+///
+/// # Errors
+/// Returns an error if hashing fails, the username is already taken, or the
+/// insert fails.
+pub(crate) fn create_user(pool: &DbPool, username: &str, password: &str, is_admin: bool, now: i64) -> Result<User> {
+    let password_hash = hash_password(password)?;
+
+    let mut conn = pool.get()?;
+    diesel::insert_into(users::table)
+        .values(&NewUser {
+            username: username.to_string(),
+            password_hash,
+            is_admin,
+            created_at: now,
+        })
+        .execute(&mut conn)
+        .context("failed to insert user")?;
+
+    users::table
+        .filter(users::username.eq(username))
+        .select(User::as_select())
+        .first(&mut conn)
+        .context("failed to load newly created user")
+}
+
+/// Returns `true` if `pool` has no users yet, i.e. `--bootstrap-admin-*`
+/// should be allowed to create the first account.
+///
+/// # Errors
+/// Returns an error if the query fails.
+pub(crate) fn has_no_users(pool: &DbPool) -> Result<bool> {
+    let mut conn = pool.get()?;
+    let count: i64 = users::table.count().get_result(&mut conn)?;
+    Ok(count == 0)
+}
+
+/// Verifies `username`/`password` against the stored argon2 hash, returning
+/// the matching user on success.
+///
+/// # Errors
+/// Returns an error if no such user exists or the password doesn't match.
+pub(crate) fn verify_credentials(pool: &DbPool, username: &str, password: &str) -> Result<User> {
+    let mut conn = pool.get()?;
+    let user = users::table
+        .filter(users::username.eq(username))
+        .select(User::as_select())
+        .first(&mut conn)
+        .context("invalid username or password")?;
+
+    let parsed_hash = PasswordHash::new(&user.password_hash).context("stored password hash is corrupt")?;
+    Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .map_err(|_| anyhow::anyhow!("invalid username or password"))?;
+
+    Ok(user)
+}
+
+/// Records a freshly-issued JWT's `jti` so it can be checked/revoked later.
+///
+/// # Errors
+/// Returns an error if the insert fails.
+pub(crate) fn record_issued_token(pool: &DbPool, user_id: i32, jti: &str, now: i64, expires_at: i64) -> Result<()> {
+    let mut conn = pool.get()?;
+    diesel::insert_into(api_tokens::table)
+        .values(&NewApiToken {
+            user_id,
+            jti: jti.to_string(),
+            created_at: now,
+            expires_at,
+        })
+        .execute(&mut conn)
+        .context("failed to record issued token")?;
+    Ok(())
+}
+
+/// Checks that `jti` was issued by us, hasn't been revoked, and hasn't
+/// expired (by the database's own `expires_at`, independent of the JWT's
+/// own `exp` claim the caller already validated).
+///
+/// # Errors
+/// Returns an error if the token is unknown, revoked, or expired.
+pub(crate) fn check_token_valid(pool: &DbPool, jti: &str, now: i64) -> Result<()> {
+    let mut conn = pool.get()?;
+    let token = api_tokens::table
+        .filter(api_tokens::jti.eq(jti))
+        .select(ApiToken::as_select())
+        .first(&mut conn)
+        .context("unknown token")?;
+
+    if token.revoked_at.is_some() {
+        bail!("token has been revoked");
+    }
+    if token.expires_at < now {
+        bail!("token has expired");
+    }
+
+    Ok(())
+}
+
+/// Marks `jti` as revoked as of `now`, so `check_token_valid` rejects it on
+/// every subsequent request even if its `exp` claim hasn't passed yet.
+///
+/// # Errors
+/// Returns an error if the update fails.
+pub(crate) fn revoke_token(pool: &DbPool, jti: &str, now: i64) -> Result<()> {
+    let mut conn = pool.get()?;
+    diesel::update(api_tokens::table.filter(api_tokens::jti.eq(jti)))
+        .set(api_tokens::revoked_at.eq(Some(now)))
+        .execute(&mut conn)
+        .context("failed to revoke token")?;
+    Ok(())
+}
+
+fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow::anyhow!("failed to hash password: {e}"))
+}
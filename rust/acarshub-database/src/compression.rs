@@ -0,0 +1,87 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transparent zstd compression for the large, highly-repetitive `Text`
+//! blobs (`msg_text`, `libacars`) stored by busy receivers.
+//!
+//! Compressed values are stored as `"\0ZSTD1:<base64>"`, where the base64
+//! payload is `<zstd frame><4-byte little-endian CRC32 of the uncompressed
+//! bytes>`. The leading NUL byte, not just the `ZSTD1:` text, is what marks a
+//! value as compressed: ACARS/VDLM/HFDL message text is teletype-era data
+//! that never legitimately contains embedded NUL bytes, so a real message
+//! can't collide with the tag no matter what printable text it starts with.
+//! Rows written before this feature existed have no such prefix, so they're
+//! detected and passed through untouched.
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+const PREFIX: &str = "\0ZSTD1:";
+const CHECKSUM_LEN: usize = 4;
+
+/// Compresses `value` if it's at least `threshold` bytes, otherwise returns
+/// it unchanged (small values aren't worth the per-row overhead).
+pub fn compress_if_large(value: &str, threshold: usize, level: i32) -> Result<String> {
+    if value.len() < threshold {
+        return Ok(value.to_string());
+    }
+
+    let compressed = zstd::encode_all(value.as_bytes(), level).context("zstd compression failed")?;
+    let checksum = crc32fast::hash(value.as_bytes());
+
+    let mut payload = compressed;
+    payload.extend_from_slice(&checksum.to_le_bytes());
+
+    Ok(format!("{PREFIX}{}", BASE64.encode(payload)))
+}
+
+/// Decompresses `stored` if it carries the leading NUL + `ZSTD1:` tag,
+/// otherwise returns it unchanged. `rowid`/`field` are only used for
+/// diagnostics on checksum mismatch.
+pub fn decompress_if_tagged(stored: &str, rowid: i32, field: &str) -> Result<String> {
+    let Some(encoded) = stored.strip_prefix(PREFIX) else {
+        return Ok(stored.to_string());
+    };
+
+    let payload = BASE64
+        .decode(encoded)
+        .with_context(|| format!("row {rowid} field {field}: invalid base64"))?;
+
+    if payload.len() < CHECKSUM_LEN {
+        bail!("row {rowid} field {field}: compressed payload too short");
+    }
+
+    let (compressed, checksum_bytes) = payload.split_at(payload.len() - CHECKSUM_LEN);
+    let expected_checksum = u32::from_le_bytes(
+        checksum_bytes
+            .try_into()
+            .context("checksum slice was not 4 bytes")?,
+    );
+
+    let decompressed =
+        zstd::decode_all(compressed).with_context(|| format!("row {rowid} field {field}: zstd decode failed"))?;
+
+    let actual_checksum = crc32fast::hash(&decompressed);
+    if actual_checksum != expected_checksum {
+        error!(
+            "row {rowid} field {field}: checksum mismatch (expected {expected_checksum:08x}, got {actual_checksum:08x}) - data is corrupt"
+        );
+        bail!("row {rowid} field {field}: checksum mismatch, data is corrupt");
+    }
+
+    String::from_utf8(decompressed).with_context(|| format!("row {rowid} field {field}: decompressed bytes were not valid UTF-8"))
+}
@@ -14,44 +14,182 @@
 // You should have received a copy of the GNU General Public License
 // along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use acars_vdlm2_parser::{AcarsVdlm2Message, acars::AcarsMessage};
+use acarshub_common::{FoundMessage, IngestedMessage};
 use conv::ConvUtil;
-use parking_lot::FairMutex;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::{Instant, timeout_at};
 
+use crate::alerts::AlertMatch;
 use crate::{AcarsHubDatabase, models::NewMessage};
+
+/// Default number of messages accumulated before a batch is flushed to the
+/// database, unless overridden via `Input`'s `--db-batch-size`.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Default maximum time a partial batch waits for more messages before it's
+/// flushed anyway, unless overridden via `Input`'s `--db-flush-interval-ms`.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct DatabaseListener {
-    database: Arc<FairMutex<AcarsHubDatabase>>,
+    database: Arc<AcarsHubDatabase>,
+    batch_size: usize,
+    flush_interval: Duration,
+    alert_terms: Vec<String>,
+    alert_tx: broadcast::Sender<AlertMatch>,
 }
 
 impl DatabaseListener {
     /// Creates a new instance of `DatabaseListener`.
     /// # Arguments
     /// * `database` - A reference to the `AcarsHubDatabase` instance.
-    pub const fn new(database: Arc<FairMutex<AcarsHubDatabase>>) -> Self {
-        Self { database }
+    /// * `batch_size` - Maximum number of messages grouped into one insert transaction.
+    /// * `flush_interval` - Maximum time a partial batch waits before being flushed anyway.
+    /// * `alert_terms` - Watched terms (from `Input`'s `--alert-terms`) checked against every
+    ///   decoded message's text before it's inserted.
+    /// * `alert_tx` - Sender a matching [`AlertMatch`] is broadcast on; see
+    ///   [`crate::alerts::start_alert_sinks`].
+    pub const fn new(
+        database: Arc<AcarsHubDatabase>,
+        batch_size: usize,
+        flush_interval: Duration,
+        alert_terms: Vec<String>,
+        alert_tx: broadcast::Sender<AlertMatch>,
+    ) -> Self {
+        Self {
+            database,
+            batch_size,
+            flush_interval,
+            alert_terms,
+            alert_tx,
+        }
     }
 
-    /// Starts the database listener.
-    pub fn start(&self, mut receiver: UnboundedReceiver<AcarsVdlm2Message>) {
+    /// Starts the database listener, batching incoming messages so a burst of
+    /// traffic costs one transaction instead of one per message.
+    pub fn start(&self, receiver: UnboundedReceiver<FoundMessage>) {
         let database = self.database.clone();
-        tokio::spawn(async move {
-            while let Some(message) = receiver.recv().await {
-                // Process the message and store it in the database
-
-                let message_processed = message.to_message();
-                if let Some(ref message) = message_processed {
-                    let mut db: parking_lot::lock_api::MutexGuard<
-                        '_,
-                        parking_lot::RawFairMutex,
-                        AcarsHubDatabase,
-                    > = database.lock();
-                    db.insert_message(message);
-                }
+        let batch_size = self.batch_size.max(1);
+        let flush_interval = self.flush_interval;
+        let alert_terms = self.alert_terms.clone();
+        let alert_tx = self.alert_tx.clone();
+        tokio::spawn(run_batch_listener(
+            database,
+            receiver,
+            batch_size,
+            flush_interval,
+            alert_terms,
+            alert_tx,
+        ));
+    }
+}
+
+/// Drains `receiver` into batches of up to `batch_size` messages, flushing
+/// early if `flush_interval` elapses before the batch fills up. Any partial
+/// batch still buffered when the channel closes is flushed before exiting.
+async fn run_batch_listener(
+    database: Arc<AcarsHubDatabase>,
+    mut receiver: UnboundedReceiver<FoundMessage>,
+    batch_size: usize,
+    flush_interval: Duration,
+    alert_terms: Vec<String>,
+    alert_tx: broadcast::Sender<AlertMatch>,
+) {
+    let mut buffer: Vec<FoundMessage> = Vec::with_capacity(batch_size);
+
+    loop {
+        match receiver.recv().await {
+            Some(message) => buffer.push(message),
+            None => break,
+        }
+
+        let deadline = Instant::now() + flush_interval;
+        while buffer.len() < batch_size {
+            match timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(message)) => buffer.push(message),
+                Ok(None) | Err(_) => break,
             }
-        });
+        }
+
+        flush_batch(&database, &alert_terms, &alert_tx, &buffer);
+        buffer.clear();
+    }
+
+    if !buffer.is_empty() {
+        flush_batch(&database, &alert_terms, &alert_tx, &buffer);
+    }
+
+    info!("Database listener channel closed, batch loop exiting");
+}
+
+fn flush_batch(
+    database: &AcarsHubDatabase,
+    alert_terms: &[String],
+    alert_tx: &broadcast::Sender<AlertMatch>,
+    batch: &[FoundMessage],
+) {
+    let messages: Vec<NewMessage> = batch.iter().filter_map(IntoMessage::to_message).collect();
+
+    if messages.is_empty() {
+        return;
+    }
+
+    check_alert_terms(database, alert_terms, alert_tx, &messages);
+
+    match database.insert_messages(&messages) {
+        Ok(()) => debug!("Inserted batch of {} messages", messages.len()),
+        Err(e) => error!("Error inserting batch of {} messages: {e}", messages.len()),
+    }
+}
+
+/// Checks each message's text against `alert_terms` (net of anything in
+/// `ignore_alert_terms`), recording a hit in `alert_stats` and broadcasting
+/// an [`AlertMatch`] for every match. Runs before the batch is inserted, so
+/// `AlertMatch::message_id` is always `None`; see its doc comment.
+fn check_alert_terms(
+    database: &AcarsHubDatabase,
+    alert_terms: &[String],
+    alert_tx: &broadcast::Sender<AlertMatch>,
+    messages: &[NewMessage],
+) {
+    if alert_terms.is_empty() {
+        return;
+    }
+
+    let ignored: HashSet<String> = match database.list_ignore_alert_terms() {
+        Ok(terms) => terms.into_iter().filter_map(|t| t.term).map(|t| t.to_lowercase()).collect(),
+        Err(e) => {
+            error!("Failed to load ignore_alert_terms, skipping alert matching for this batch: {e}");
+            return;
+        }
+    };
+
+    for message in messages {
+        let haystack = message.msg_text.to_lowercase();
+
+        for term in alert_terms {
+            let needle = term.to_lowercase();
+            if ignored.contains(&needle) || !haystack.contains(&needle) {
+                continue;
+            }
+
+            if let Err(e) = database.record_alert_hit(term) {
+                error!("Failed to record alert hit for term {term:?}: {e}");
+            }
+
+            // Only fails once every receiver has been dropped, i.e. no sinks
+            // are configured; nothing to act on either way.
+            let _ = alert_tx.send(AlertMatch {
+                term: term.clone(),
+                match_type: "contains".to_string(),
+                message_id: None,
+            });
+        }
     }
 }
 
@@ -60,32 +198,39 @@ pub trait IntoMessage {
     fn acars_message(&self, msg: AcarsMessage) -> NewMessage;
 }
 
-impl IntoMessage for AcarsVdlm2Message {
+impl IntoMessage for FoundMessage {
     fn to_message(&self) -> Option<NewMessage> {
-        match self {
-            Self::AcarsMessage(msg) => Some(self.acars_message(msg.clone())),
-            Self::Vdlm2Message(_msg) => {
+        match &self.message {
+            IngestedMessage::Acars(AcarsVdlm2Message::AcarsMessage(msg)) => Some(self.acars_message(msg.clone())),
+            IngestedMessage::Acars(AcarsVdlm2Message::Vdlm2Message(_msg)) => {
                 warn!("Vdlm2Message not yet implemented");
                 None
             }
-            Self::HfdlMessage(_msg) => {
+            IngestedMessage::Acars(AcarsVdlm2Message::HfdlMessage(_msg)) => {
                 warn!("HfdlMessage not yet implemented");
                 None
             }
-            Self::IrdmMessage(_msg) => {
+            IngestedMessage::Acars(AcarsVdlm2Message::IrdmMessage(_msg)) => {
                 warn!("IrdmMessage not yet implemented");
                 None
             }
-            Self::ImslMessage(_msg) => {
+            IngestedMessage::Acars(AcarsVdlm2Message::ImslMessage(_msg)) => {
                 warn!("ImslMessage not yet implemented");
                 None
             }
+            // The `messages` table has no columns for ADS-B's position/identity
+            // fields; storing it meaningfully needs its own table, which is out
+            // of scope here (see the request that introduced `IngestedMessage`).
+            IngestedMessage::Adsb(_msg) => {
+                warn!("Adsb messages are not yet stored");
+                None
+            }
         }
     }
 
     fn acars_message(&self, msg: AcarsMessage) -> NewMessage {
         NewMessage {
-            message_type: "ACARS".to_string(),
+            message_type: self.protocol.to_string(),
             msg_time: msg
                 .timestamp
                 .unwrap_or_default()
@@ -0,0 +1,275 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! One-time, resumable migration of the legacy Python-era `messages.db`
+//! into the current diesel-managed database.
+//!
+//! The legacy schema has exactly one table worth carrying forward,
+//! `messages`, and its column names are the same ones the Rust rewrite
+//! kept (`toaddr`, `fromaddr`, `gtout`, `wlin`, ...). `count`, `level`,
+//! `freqs`, and `alert_stats` are aggregates the application derives from
+//! `messages` as it runs, not source data, so they're rebuilt naturally
+//! and aren't migrated here.
+//!
+//! Progress is checkpointed in a small `legacy_migration_checkpoint` table
+//! in the *new* database, keyed on the legacy file's rowid, so a crash or
+//! restart partway through resumes instead of re-copying from scratch.
+
+use anyhow::{Context, Result, bail};
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+
+use crate::models::NewMessage;
+use crate::schema::messages;
+
+/// Legacy rows are copied this many at a time, each inside its own
+/// transaction, so a single failure doesn't discard all prior progress.
+const BATCH_SIZE: i64 = 500;
+
+#[derive(QueryableByName)]
+struct LegacyMessage {
+    #[diesel(sql_type = BigInt)]
+    rowid: i64,
+    #[diesel(sql_type = Text)]
+    message_type: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    msg_time: i32,
+    #[diesel(sql_type = Text)]
+    station_id: String,
+    #[diesel(sql_type = Text)]
+    toaddr: String,
+    #[diesel(sql_type = Text)]
+    fromaddr: String,
+    #[diesel(sql_type = Text)]
+    depa: String,
+    #[diesel(sql_type = Text)]
+    dsta: String,
+    #[diesel(sql_type = Text)]
+    eta: String,
+    #[diesel(sql_type = Text)]
+    gtout: String,
+    #[diesel(sql_type = Text)]
+    gtin: String,
+    #[diesel(sql_type = Text)]
+    wloff: String,
+    #[diesel(sql_type = Text)]
+    wlin: String,
+    #[diesel(sql_type = Text)]
+    lat: String,
+    #[diesel(sql_type = Text)]
+    lon: String,
+    #[diesel(sql_type = Text)]
+    alt: String,
+    #[diesel(sql_type = Text)]
+    msg_text: String,
+    #[diesel(sql_type = Text)]
+    tail: String,
+    #[diesel(sql_type = Text)]
+    flight: String,
+    #[diesel(sql_type = Text)]
+    icao: String,
+    #[diesel(sql_type = Text)]
+    freq: String,
+    #[diesel(sql_type = Text)]
+    ack: String,
+    #[diesel(sql_type = Text)]
+    mode: String,
+    #[diesel(sql_type = Text)]
+    label: String,
+    #[diesel(sql_type = Text)]
+    block_id: String,
+    #[diesel(sql_type = Text)]
+    msgno: String,
+    #[diesel(sql_type = Text)]
+    is_response: String,
+    #[diesel(sql_type = Text)]
+    is_onground: String,
+    #[diesel(sql_type = Text)]
+    error: String,
+    #[diesel(sql_type = Text)]
+    libacars: String,
+    #[diesel(sql_type = Text)]
+    level: String,
+}
+
+impl From<LegacyMessage> for NewMessage {
+    fn from(row: LegacyMessage) -> Self {
+        Self {
+            message_type: row.message_type,
+            msg_time: row.msg_time,
+            station_id: row.station_id,
+            toaddr: row.toaddr,
+            fromaddr: row.fromaddr,
+            depa: row.depa,
+            dsta: row.dsta,
+            eta: row.eta,
+            gtout: row.gtout,
+            gtin: row.gtin,
+            wloff: row.wloff,
+            wlin: row.wlin,
+            lat: row.lat,
+            lon: row.lon,
+            alt: row.alt,
+            msg_text: row.msg_text,
+            tail: row.tail,
+            flight: row.flight,
+            icao: row.icao,
+            freq: row.freq,
+            ack: row.ack,
+            mode: row.mode,
+            label: row.label,
+            block_id: row.block_id,
+            msgno: row.msgno,
+            is_response: row.is_response,
+            is_onground: row.is_onground,
+            error: row.error,
+            libacars: row.libacars,
+            level: row.level,
+        }
+    }
+}
+
+#[derive(QueryableByName)]
+struct RowCount {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct Checkpoint {
+    #[diesel(sql_type = BigInt)]
+    last_rowid: i64,
+}
+
+/// If `legacy_path` exists, copies every row from its `messages` table into
+/// `new_conn`'s `messages` table that hasn't already been copied, then
+/// renames `legacy_path` to `<legacy_path>.migrated` once the checkpoint has
+/// caught up to the legacy table's last rowid. Returns `Ok(())` (doing
+/// nothing) if `legacy_path` doesn't exist.
+///
+/// # Errors
+/// Returns an error if the legacy file can't be opened, a batch fails to
+/// copy, or the checkpoint hasn't reached the legacy database's last rowid
+/// (in which case the legacy file is left in place for investigation).
+pub fn migrate_if_present(legacy_path: &str, new_conn: &mut SqliteConnection) -> Result<()> {
+    if !std::path::Path::new(legacy_path).exists() {
+        return Ok(());
+    }
+
+    info!("Legacy database found at {legacy_path}, migrating it into the new database");
+
+    new_conn
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS legacy_migration_checkpoint (
+                legacy_path TEXT PRIMARY KEY,
+                last_rowid BIGINT NOT NULL
+            );",
+        )
+        .context("failed to create legacy_migration_checkpoint table")?;
+
+    let mut legacy_conn = SqliteConnection::establish(legacy_path)
+        .with_context(|| format!("failed to open legacy database at {legacy_path}"))?;
+
+    let legacy_count: i64 = diesel::sql_query("SELECT COUNT(*) AS count FROM messages")
+        .get_result::<RowCount>(&mut legacy_conn)
+        .context("failed to count rows in legacy database")?
+        .count;
+
+    // The post-migration check below needs to know how far the legacy table
+    // actually goes, not how many rows the *new* database happens to have --
+    // the new database keeps accepting live traffic while this runs, so its
+    // row count is not a useful proxy for migration progress.
+    let legacy_max_rowid: i64 = diesel::sql_query("SELECT COALESCE(MAX(rowid), 0) AS count FROM messages")
+        .get_result::<RowCount>(&mut legacy_conn)
+        .context("failed to read the max rowid from the legacy database")?
+        .count;
+
+    let mut last_rowid: i64 = diesel::sql_query(
+        "SELECT last_rowid FROM legacy_migration_checkpoint WHERE legacy_path = ?",
+    )
+    .bind::<Text, _>(legacy_path)
+    .get_result::<Checkpoint>(new_conn)
+    .map(|c| c.last_rowid)
+    .unwrap_or(0);
+
+    if last_rowid > 0 {
+        info!("Resuming legacy migration from rowid {last_rowid}");
+    }
+
+    loop {
+        let batch = diesel::sql_query(
+            "SELECT rowid, message_type, msg_time, station_id, toaddr, fromaddr, depa, dsta, \
+             eta, gtout, gtin, wloff, wlin, lat, lon, alt, msg_text, tail, flight, icao, freq, \
+             ack, mode, label, block_id, msgno, is_response, is_onground, error, libacars, level \
+             FROM messages WHERE rowid > ? ORDER BY rowid LIMIT ?",
+        )
+        .bind::<BigInt, _>(last_rowid)
+        .bind::<BigInt, _>(BATCH_SIZE)
+        .load::<LegacyMessage>(&mut legacy_conn)
+        .context("failed to read a batch of legacy rows")?;
+
+        if batch.is_empty() {
+            break;
+        }
+
+        let batch_last_rowid = batch
+            .last()
+            .map(|row| row.rowid)
+            .unwrap_or(last_rowid);
+        let new_rows: Vec<NewMessage> = batch.into_iter().map(NewMessage::from).collect();
+
+        new_conn.transaction(|conn| -> Result<()> {
+            diesel::insert_into(messages::table)
+                .values(&new_rows)
+                .execute(conn)
+                .context("failed to insert a batch of migrated rows")?;
+
+            diesel::sql_query(
+                "INSERT INTO legacy_migration_checkpoint (legacy_path, last_rowid) VALUES (?, ?) \
+                 ON CONFLICT(legacy_path) DO UPDATE SET last_rowid = excluded.last_rowid",
+            )
+            .bind::<Text, _>(legacy_path)
+            .bind::<BigInt, _>(batch_last_rowid)
+            .execute(conn)
+            .context("failed to persist migration checkpoint")?;
+
+            Ok(())
+        })?;
+
+        last_rowid = batch_last_rowid;
+        info!("Migrated legacy rows up to rowid {last_rowid} of {legacy_count} total");
+    }
+
+    if last_rowid < legacy_max_rowid {
+        bail!(
+            "legacy migration incomplete: checkpoint is at rowid {last_rowid}, legacy database has rows up to rowid {legacy_max_rowid}; leaving {legacy_path} in place"
+        );
+    }
+
+    let migrated_path = format!("{legacy_path}.migrated");
+    std::fs::rename(legacy_path, &migrated_path)
+        .with_context(|| format!("failed to rename {legacy_path} to {migrated_path}"))?;
+
+    diesel::sql_query("DELETE FROM legacy_migration_checkpoint WHERE legacy_path = ?")
+        .bind::<Text, _>(legacy_path)
+        .execute(new_conn)
+        .context("failed to clear migration checkpoint")?;
+
+    info!("Legacy migration complete: {legacy_count} rows copied, old database moved to {migrated_path}");
+
+    Ok(())
+}
@@ -31,58 +31,383 @@ extern crate diesel;
 #[macro_use]
 extern crate tracing;
 
+pub mod alerts;
+mod auth;
+pub mod db_listener;
+mod compression;
+mod legacy_migration;
+pub mod models;
+pub mod schema;
+
 use anyhow::Result;
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
-use std::env;
+use models::{
+    AlertStat, IgnoreAlertTerm, Messages, MessagesSaved, NewIgnoreAlertTerm, NewMessage, NewMessagesSaved, User,
+};
+use schema::{alert_stats, ignore_alert_terms, messages, messages_saved};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
+/// Fields at or above this size (in bytes) get zstd-compressed before being
+/// stored, unless overridden via `Input`'s `--compression-threshold`.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 128;
+
+/// Default zstd compression level, unless overridden via `Input`'s
+/// `--compression-level`.
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// Default number of pooled `SqliteConnection`s, unless overridden via
+/// `Input`'s `--db-pool-size`.
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// Default `busy_timeout` (in milliseconds) applied to every pooled
+/// connection, unless overridden via `Input`'s `--db-busy-timeout-ms`.
+pub const DEFAULT_BUSY_TIMEOUT_MS: u32 = 5000;
+
+pub(crate) type DbPool = Pool<ConnectionManager<SqliteConnection>>;
+
+/// Runs on every connection as it's checked into the pool, putting the
+/// database in WAL journal mode (so readers and the writer don't block each
+/// other) and giving concurrent writers a `busy_timeout` to wait out
+/// `SQLITE_BUSY` instead of failing immediately.
+#[derive(Debug)]
+struct ConnectionCustomizer {
+    busy_timeout_ms: u32,
+}
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(&format!(
+            "PRAGMA journal_mode = WAL; PRAGMA busy_timeout = {};",
+            self.busy_timeout_ms
+        ))
+        .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
 pub struct AcarsHubDatabase {
-    connection: SqliteConnection,
+    pool: DbPool,
+    compression_threshold: usize,
+    compression_level: i32,
 }
 
 impl AcarsHubDatabase {
-    /// Creates a new instance of `AcarsHubDatabase` and establishes a connection to the `SQLite` database.
-    /// # Returns
-    /// Returns a `Result` containing the `AcarsHubDatabase` instance if successful, or an error if the connection fails.
-    ///
+    /// Creates a new instance of `AcarsHubDatabase`, building a pooled
+    /// connection to the `SQLite` database so inserts and reads no longer
+    /// serialize through a single connection.
     /// # Errors
-    /// If the connection to the database fails, an error is returned.
-    pub fn new() -> Result<Self> {
-        let mut database_url = env::var("DATABASE_URL")
-            .unwrap_or_else(|_| "/opt/acarshub/messages.sqlite".to_string());
+    /// If the pool can't be built, or the initial connection/migrations fail.
+    pub fn new(
+        database_url: &str,
+        pool_size: u32,
+        busy_timeout_ms: u32,
+        compression_threshold: usize,
+        compression_level: i32,
+    ) -> Result<Self> {
+        const LEGACY_DATABASE_PATH: &str = "/run/acarshub/messages.db";
 
-        // we need to see if we're on an old version of ACARS Hub. If /run/acarshub/messages.db exists, we need to use that and
-        // inform the user that they need to migrate their database
+        info!("Connecting to database at {database_url} with a pool of {pool_size} connections");
 
-        if std::path::Path::new("/run/acarshub/messages.db").exists() {
-            database_url = "/run/acarshub/messages.db".to_string();
-            warn!("Using old database at /run/acarshub/messages.db. Please migrate your database.");
+        let manager = ConnectionManager::<SqliteConnection>::new(database_url);
+        let pool = Pool::builder()
+            .max_size(pool_size)
+            .connection_customizer(Box::new(ConnectionCustomizer { busy_timeout_ms }))
+            .build(manager)?;
+
+        // Run the migrations once, up front, on a connection checked out from the pool.
+        let mut conn = pool.get()?;
+        run_migrations(&mut conn)?;
+
+        // If we're upgrading from a pre-diesel install, copy the legacy
+        // database's rows into the new one before serving any traffic.
+        if let Err(e) = legacy_migration::migrate_if_present(LEGACY_DATABASE_PATH, &mut conn) {
+            error!("Failed to migrate legacy database at {LEGACY_DATABASE_PATH}: {e}");
         }
 
-        info!("Connecting to database at {database_url}");
+        Ok(Self {
+            pool,
+            compression_threshold,
+            compression_level,
+        })
+    }
 
-        let mut conn = establish_connection(&database_url)?;
+    /// Compresses `msg_text`/`libacars` (if large enough) and inserts every
+    /// message in `batch` inside a single transaction, so a burst of
+    /// messages costs one fsync instead of one per row.
+    ///
+    /// # Errors
+    /// Returns an error if a connection can't be checked out of the pool,
+    /// compression fails, or the insert fails.
+    pub fn insert_messages(&self, batch: &[NewMessage]) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
 
-        // Run the migrations
-        run_migrations(&mut conn)?;
+        let compressed = batch
+            .iter()
+            .map(|message| self.compress_fields(message))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut conn = self.pool.get()?;
+        conn.transaction(|conn| diesel::insert_into(messages::table).values(&compressed).execute(conn))?;
 
-        Ok(Self { connection: conn })
+        Ok(())
     }
-}
 
-fn establish_connection(database_url: &str) -> Result<SqliteConnection> {
-    match SqliteConnection::establish(database_url) {
-        Ok(conn) => {
-            debug!("Connected to database at {database_url}");
-            Ok(conn)
-        }
-        Err(e) => {
-            error!("Error connecting to database: {e}");
-            Err(e.into())
-        }
+    fn compress_fields(&self, message: &NewMessage) -> Result<NewMessage> {
+        let mut message = message.clone();
+        message.msg_text =
+            compression::compress_if_large(&message.msg_text, self.compression_threshold, self.compression_level)?;
+        message.libacars =
+            compression::compress_if_large(&message.libacars, self.compression_threshold, self.compression_level)?;
+        Ok(message)
+    }
+
+    /// Fetches a single message by id, transparently decompressing
+    /// `msg_text`/`libacars`.
+    ///
+    /// # Errors
+    /// Returns an error if the row doesn't exist, the query fails, or a
+    /// compressed field fails its integrity check on decompression.
+    pub fn get_message(&self, message_id: i32) -> Result<Messages> {
+        let mut conn = self.pool.get()?;
+        let message = messages::table
+            .find(message_id)
+            .select(Messages::as_select())
+            .first(&mut conn)?;
+
+        decompress_message(message)
     }
+
+    /// Fetches a single starred/saved message by id, transparently
+    /// decompressing `msg_text`/`libacars` the same way as [`Self::get_message`].
+    ///
+    /// # Errors
+    /// Returns an error if the row doesn't exist, the query fails, or a
+    /// compressed field fails its integrity check on decompression.
+    pub fn get_saved_message(&self, message_id: i32) -> Result<MessagesSaved> {
+        let mut conn = self.pool.get()?;
+        let message = messages_saved::table
+            .find(message_id)
+            .select(MessagesSaved::as_select())
+            .first(&mut conn)?;
+
+        decompress_saved_message(message)
+    }
+
+    /// Copies `message_id` out of `messages` into `messages_saved`, tagged
+    /// with `term`/`type_of_match` (the alert term that matched, if this is
+    /// being saved in response to an alert rather than a manual star).
+    ///
+    /// # Errors
+    /// Returns an error if `message_id` doesn't exist or the insert fails.
+    pub fn save_message(&self, message_id: i32, term: &str, type_of_match: &str) -> Result<()> {
+        let message = self.get_message(message_id)?;
+        let compressed = self.compress_fields(&NewMessage {
+            message_type: message.message_type,
+            msg_time: message.msg_time,
+            station_id: message.station_id,
+            toaddr: message.toaddr,
+            fromaddr: message.fromaddr,
+            depa: message.depa,
+            dsta: message.dsta,
+            eta: message.eta,
+            gtout: message.gtout,
+            gtin: message.gtin,
+            wloff: message.wloff,
+            wlin: message.wlin,
+            lat: message.lat,
+            lon: message.lon,
+            alt: message.alt,
+            msg_text: message.msg_text,
+            tail: message.tail,
+            flight: message.flight,
+            icao: message.icao,
+            freq: message.freq,
+            ack: message.ack,
+            mode: message.mode,
+            label: message.label,
+            block_id: message.block_id,
+            msgno: message.msgno,
+            is_response: message.is_response,
+            is_onground: message.is_onground,
+            error: message.error,
+            libacars: message.libacars,
+            level: message.level,
+        })?;
+
+        let mut conn = self.pool.get()?;
+        diesel::insert_into(messages_saved::table)
+            .values(&NewMessagesSaved {
+                message_type: compressed.message_type,
+                msg_time: compressed.msg_time,
+                station_id: compressed.station_id,
+                toaddr: compressed.toaddr,
+                fromaddr: compressed.fromaddr,
+                depa: compressed.depa,
+                dsta: compressed.dsta,
+                eta: compressed.eta,
+                gtout: compressed.gtout,
+                gtin: compressed.gtin,
+                wloff: compressed.wloff,
+                wlin: compressed.wlin,
+                lat: compressed.lat,
+                lon: compressed.lon,
+                alt: compressed.alt,
+                msg_text: compressed.msg_text,
+                tail: compressed.tail,
+                flight: compressed.flight,
+                icao: compressed.icao,
+                freq: compressed.freq,
+                ack: compressed.ack,
+                mode: compressed.mode,
+                label: compressed.label,
+                block_id: compressed.block_id,
+                msgno: compressed.msgno,
+                is_response: compressed.is_response,
+                is_onground: compressed.is_onground,
+                error: compressed.error,
+                libacars: compressed.libacars,
+                level: compressed.level,
+                term: term.to_string(),
+                type_of_match: type_of_match.to_string(),
+            })
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Adds `term` to `ignore_alert_terms`, so future alert matching skips it.
+    ///
+    /// # Errors
+    /// Returns an error if the insert fails.
+    pub fn add_ignore_alert_term(&self, term: &str) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        diesel::insert_into(ignore_alert_terms::table)
+            .values(&NewIgnoreAlertTerm { term: term.to_string() })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Removes an `ignore_alert_terms` row by id.
+    ///
+    /// # Errors
+    /// Returns an error if the delete fails.
+    pub fn remove_ignore_alert_term(&self, id: i32) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        diesel::delete(ignore_alert_terms::table.find(id)).execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Lists every `ignore_alert_terms` row.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails.
+    pub fn list_ignore_alert_terms(&self) -> Result<Vec<IgnoreAlertTerm>> {
+        let mut conn = self.pool.get()?;
+        Ok(ignore_alert_terms::table.load(&mut conn)?)
+    }
+
+    /// Records a watched-term match in `alert_stats`, inserting a fresh row
+    /// (count 1) the first time `term` fires and incrementing it thereafter.
+    /// Called by [`db_listener`] alongside broadcasting the matching
+    /// [`alerts::AlertMatch`] event.
+    ///
+    /// # Errors
+    /// Returns an error if the lookup, update, or insert fails.
+    pub fn record_alert_hit(&self, term: &str) -> Result<()> {
+        let mut conn = self.pool.get()?;
+
+        conn.transaction(|conn| {
+            let existing = alert_stats::table
+                .filter(alert_stats::term.eq(term))
+                .first::<AlertStat>(conn)
+                .optional()?;
+
+            match existing {
+                Some(row) => {
+                    let new_count = row.count.unwrap_or(0) + 1;
+                    diesel::update(alert_stats::table.find(row.id))
+                        .set(alert_stats::count.eq(new_count))
+                        .execute(conn)?;
+                }
+                None => {
+                    diesel::insert_into(alert_stats::table)
+                        .values((alert_stats::term.eq(term), alert_stats::count.eq(1)))
+                        .execute(conn)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Creates the first user account, hashing `password` with argon2.
+    /// Intended for `--bootstrap-admin-username`/`--bootstrap-admin-password`
+    /// at startup; use [`Self::has_no_users`] to guard against clobbering an
+    /// existing account.
+    ///
+    /// # Errors
+    /// Returns an error if hashing or the insert fails, or the username is
+    /// already taken.
+    pub fn create_user(&self, username: &str, password: &str, is_admin: bool, now: i64) -> Result<User> {
+        auth::create_user(&self.pool, username, password, is_admin, now)
+    }
+
+    /// Returns `true` if no user accounts exist yet.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails.
+    pub fn has_no_users(&self) -> Result<bool> {
+        auth::has_no_users(&self.pool)
+    }
+
+    /// Verifies a login attempt against the stored argon2 hash.
+    ///
+    /// # Errors
+    /// Returns an error if the username doesn't exist or the password is wrong.
+    pub fn verify_credentials(&self, username: &str, password: &str) -> Result<User> {
+        auth::verify_credentials(&self.pool, username, password)
+    }
+
+    /// Records a freshly-issued JWT's `jti` so it can be checked/revoked later.
+    ///
+    /// # Errors
+    /// Returns an error if the insert fails.
+    pub fn record_issued_token(&self, user_id: i32, jti: &str, now: i64, expires_at: i64) -> Result<()> {
+        auth::record_issued_token(&self.pool, user_id, jti, now, expires_at)
+    }
+
+    /// Checks that `jti` is known, unrevoked, and unexpired.
+    ///
+    /// # Errors
+    /// Returns an error if the token is unknown, revoked, or expired.
+    pub fn check_token_valid(&self, jti: &str, now: i64) -> Result<()> {
+        auth::check_token_valid(&self.pool, jti, now)
+    }
+
+    /// Revokes a previously-issued token so it's rejected on its next use.
+    ///
+    /// # Errors
+    /// Returns an error if the update fails.
+    pub fn revoke_token(&self, jti: &str, now: i64) -> Result<()> {
+        auth::revoke_token(&self.pool, jti, now)
+    }
+}
+
+fn decompress_message(mut message: Messages) -> Result<Messages> {
+    message.msg_text = compression::decompress_if_tagged(&message.msg_text, message.id, "msg_text")?;
+    message.libacars = compression::decompress_if_tagged(&message.libacars, message.id, "libacars")?;
+    Ok(message)
+}
+
+fn decompress_saved_message(mut message: MessagesSaved) -> Result<MessagesSaved> {
+    message.msg_text = compression::decompress_if_tagged(&message.msg_text, message.id, "msg_text")?;
+    message.libacars = compression::decompress_if_tagged(&message.libacars, message.id, "libacars")?;
+    Ok(message)
 }
 
 fn run_migrations(conn: &mut SqliteConnection) -> Result<()> {
@@ -59,6 +59,12 @@ pub struct IgnoreAlertTerm {
     pub term: Option<String>,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::ignore_alert_terms)]
+pub struct NewIgnoreAlertTerm {
+    pub term: String,
+}
+
 #[derive(Queryable, Debug, Identifiable)]
 #[diesel(table_name = crate::schema::level)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
@@ -69,7 +75,7 @@ pub struct Level {
     pub count: Option<i32>,
 }
 
-#[derive(Insertable)]
+#[derive(Insertable, Clone)]
 #[diesel(table_name = crate::schema::messages)]
 pub struct NewMessage {
     pub message_type: String,
@@ -104,7 +110,7 @@ pub struct NewMessage {
     pub level: String,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::messages)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Messages {
@@ -194,7 +200,44 @@ pub struct MessagesFtsIdx {
     pub pgno: Option<Vec<u8>>,
 }
 
-#[derive(Queryable, Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::messages_saved)]
+pub struct NewMessagesSaved {
+    pub message_type: String,
+    pub msg_time: i32,
+    pub station_id: String,
+    pub toaddr: String,
+    pub fromaddr: String,
+    pub depa: String,
+    pub dsta: String,
+    pub eta: String,
+    pub gtout: String,
+    pub gtin: String,
+    pub wloff: String,
+    pub wlin: String,
+    pub lat: String,
+    pub lon: String,
+    pub alt: String,
+    pub msg_text: String,
+    pub tail: String,
+    pub flight: String,
+    pub icao: String,
+    pub freq: String,
+    pub ack: String,
+    pub mode: String,
+    pub label: String,
+    pub block_id: String,
+    pub msgno: String,
+    pub is_response: String,
+    pub is_onground: String,
+    pub error: String,
+    pub libacars: String,
+    pub level: String,
+    pub term: String,
+    pub type_of_match: String,
+}
+
+#[derive(Queryable, Selectable, Debug)]
 #[diesel(table_name = crate::schema::messages_saved)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct MessagesSaved {
@@ -241,3 +284,44 @@ pub struct NonloggedCount {
     pub errors: Option<i32>,
     pub good: Option<i32>,
 }
+
+#[derive(Queryable, Selectable, Debug, Identifiable)]
+#[diesel(table_name = crate::schema::users)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct User {
+    pub id: i32,
+    pub username: String,
+    pub password_hash: String,
+    pub is_admin: bool,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::users)]
+pub struct NewUser {
+    pub username: String,
+    pub password_hash: String,
+    pub is_admin: bool,
+    pub created_at: i64,
+}
+
+#[derive(Queryable, Selectable, Debug, Identifiable)]
+#[diesel(table_name = crate::schema::api_tokens)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ApiToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub jti: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub revoked_at: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::api_tokens)]
+pub struct NewApiToken {
+    pub user_id: i32,
+    pub jti: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
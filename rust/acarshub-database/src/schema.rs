@@ -8,6 +8,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    api_tokens (id) {
+        id -> Integer,
+        user_id -> Integer,
+        jti -> Text,
+        created_at -> BigInt,
+        expires_at -> BigInt,
+        revoked_at -> Nullable<BigInt>,
+    }
+}
+
 diesel::table! {
     count (id) {
         id -> Integer,
@@ -169,8 +180,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    users (id) {
+        id -> Integer,
+        username -> Text,
+        password_hash -> Text,
+        is_admin -> Bool,
+        created_at -> BigInt,
+    }
+}
+
+diesel::joinable!(api_tokens -> users (user_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
     alert_stats,
+    api_tokens,
     count,
     freqs,
     ignore_alert_terms,
@@ -183,4 +207,5 @@ diesel::allow_tables_to_appear_in_same_query!(
     messages_fts_idx,
     messages_saved,
     nonlogged_count,
+    users,
 );
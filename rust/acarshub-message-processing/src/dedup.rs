@@ -0,0 +1,137 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cross-protocol de-duplication. The same aircraft transmission often
+//! arrives more than once -- multiple receivers hearing the same burst, or
+//! both a direct decoder and an `acars_router` relay feeding this instance
+//! the same message -- so [`Deduplicator`] sits right after decode and drops
+//! anything that matches a message already seen within a configurable
+//! window.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use acars_vdlm2_parser::AcarsVdlm2Message;
+use acarshub_common::{FoundMessage, IngestedMessage};
+
+/// Window duration used unless overridden via `Input`'s `--dedup-window-ms`.
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(2);
+
+struct Entry {
+    first_seen: Instant,
+}
+
+/// A hash-indexed ring: a `HashMap<key, Entry>` gives O(1) duplicate lookup,
+/// and a `VecDeque<(Instant, key)>` ordered by insertion gives O(1) expiry
+/// eviction from the front, so neither operation degrades as the window
+/// fills up with traffic.
+pub struct Deduplicator {
+    window: Duration,
+    seen: HashMap<String, Entry>,
+    order: VecDeque<(Instant, String)>,
+    suppressed: u64,
+}
+
+impl Deduplicator {
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// Number of messages dropped as duplicates so far.
+    #[must_use]
+    pub const fn suppressed_count(&self) -> u64 {
+        self.suppressed
+    }
+
+    /// Evicts anything older than the window, then checks `found` against
+    /// what's left. Returns `None` if it's a duplicate (dropping it),
+    /// otherwise records it and returns it unchanged for the caller to
+    /// forward.
+    ///
+    /// There used to be a `Merge` policy here that folded a duplicate's
+    /// station id into the original sighting, but nothing ever read the
+    /// merged data back out -- it was discarded the instant this returned,
+    /// making `Merge` and `Drop` behaviorally identical. Dropped until
+    /// there's an actual consumer (e.g. a `stations` column the UI reads)
+    /// to wire it through to.
+    pub fn check(&mut self, found: FoundMessage) -> Option<FoundMessage> {
+        let now = Instant::now();
+        self.evict_expired(now);
+
+        let key = dedup_key(&found);
+
+        if let Some(entry) = self.seen.get(&key) {
+            if now.duration_since(entry.first_seen) < self.window {
+                self.suppressed += 1;
+                return None;
+            }
+        }
+
+        self.order.push_back((now, key.clone()));
+        self.seen.insert(key, Entry { first_seen: now });
+
+        Some(found)
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        while let Some((inserted_at, _)) = self.order.front() {
+            if now.duration_since(*inserted_at) < self.window {
+                break;
+            }
+
+            if let Some((_, key)) = self.order.pop_front() {
+                self.seen.remove(&key);
+            }
+        }
+    }
+}
+
+/// A stable key built from protocol-independent content -- aircraft
+/// registration/ICAO address, flight, the ACARS label, and the message text
+/// -- explicitly ignoring signal-level, timestamp, and frequency fields so
+/// the same transmission heard by two receivers (with different signal
+/// strength/frequency/arrival time) still collapses to one key.
+fn dedup_key(found: &FoundMessage) -> String {
+    match &found.message {
+        IngestedMessage::Acars(AcarsVdlm2Message::AcarsMessage(msg)) => format!(
+            "acars:{}:{}:{}:{}",
+            msg.icao.clone().unwrap_or_default(),
+            msg.flight.clone().unwrap_or_default(),
+            msg.label.clone().unwrap_or_default(),
+            msg.text.clone().unwrap_or_default(),
+        ),
+        // ADS-B records are keyed on ICAO address alone: unlike ACARS there's
+        // no discrete "transmission" to collapse repeats of, just a stream of
+        // position updates, so a window-scoped key per airframe is what's
+        // meaningful to de-duplicate (a receiver reporting the same airframe
+        // twice in quick succession) without losing genuinely new reports.
+        IngestedMessage::Adsb(msg) => format!("adsb:{}", msg.icao_address),
+        // VDL-M2/HFDL/Inmarsat/Iridium messages aren't unpacked anywhere
+        // else in this crate yet (see db_listener::IntoMessage's "not yet
+        // implemented" warnings), so there's no established field mapping to
+        // build a content-only key from for them. Falling back to the full
+        // decoded payload still catches exact repeats (e.g. the same
+        // acars_router relay firing twice), just without ignoring
+        // signal-level fields the way the ACARS path does.
+        other => format!("{:?}:{other:?}", found.protocol),
+    }
+}
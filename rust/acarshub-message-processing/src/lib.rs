@@ -25,72 +25,315 @@
     clippy::expect_used
 )]
 
-use acars_vdlm2_parser::{AcarsVdlm2Message, DecodeMessage};
-use core::fmt;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use acars_vdlm2_parser::DecodeMessage;
+use acarshub_common::{FoundMessage, IngestedMessage};
+use sdre_rust_adsb_parser::DecodeMessage as DecodeAdsbMessage;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::time::sleep;
 // #![warn(missing_docs)]
 #[macro_use]
 extern crate tracing;
 
-#[derive(Debug, Clone, Copy)]
-pub enum Protocols {
-    Acars,
-    Vdlm,
-    Hfdl,
-    Imsl,
-    Irdm,
-}
+mod dedup;
+mod protocol_source;
+mod redis_fanout;
 
-impl fmt::Display for Protocols {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Acars => write!(f, "ACARS"),
-            Self::Vdlm => write!(f, "VDL-M2"),
-            Self::Hfdl => write!(f, "HFDL"),
-            Self::Imsl => write!(f, "Inmarsat L-Band"),
-            Self::Irdm => write!(f, "Iridium"),
-        }
-    }
+pub use dedup::{DEFAULT_DEDUP_WINDOW, Deduplicator};
+pub use protocol_source::{ProtocolSource, SourceTransport};
+pub use redis_fanout::RedisPublisher;
+
+// Re-exported so downstream crates that historically imported `Protocols`
+// from here (before it moved into `acarshub_common`) keep working unchanged.
+pub use acarshub_common::{PortMap, Protocols};
+
+/// Default capacity of the live-message broadcast channel when none is configured.
+pub const DEFAULT_STREAM_BUFFER_SIZE: usize = 1024;
+
+/// How long a `ConnectTcp` source waits before retrying after a failed
+/// connect attempt or a dropped connection.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Size of the UDP receive buffer, large enough to hold a full-size
+/// VDLM2/HFDL JSON message (including a decoded `libacars` blob) in one
+/// datagram without truncating it.
+const UDP_RECV_BUFFER_SIZE: usize = 65_536;
+
+/// Redis fan-out configuration: either publish every decoded message to
+/// Redis, or (in subscribe mode) consume messages from Redis instead of
+/// binding UDP sockets at all.
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    pub url: String,
+    pub publish: bool,
+    pub subscribe: bool,
 }
 
-impl Protocols {
-    const fn to_tcp_udp_port(self) -> u32 {
-        match self {
-            Self::Acars => 5550,
-            Self::Vdlm => 5555,
-            Self::Hfdl => 5556,
-            Self::Imsl => 5557,
-            Self::Irdm => 5558,
-        }
-    }
+/// How a protocol's messages are ingested. Defaults to `Udp` for any
+/// feature not given an explicit entry in a `TransportConfig`.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// Bind a UDP socket on the feature's `PortMap` port (the original behavior).
+    Udp,
+    /// Accept inbound TCP connections on the feature's `PortMap` port and
+    /// decode one newline-delimited JSON message per line.
+    ListenTcp,
+    /// Dial out to `host:port` and decode one newline-delimited JSON message
+    /// per line, reconnecting with backoff if the peer drops.
+    ConnectTcp(String),
 }
 
+/// Per-protocol transport overrides; a feature with no entry here uses `Transport::Udp`.
+pub type TransportConfig = HashMap<Protocols, Transport>;
+
 pub struct AcarsHubMessageProcessing {
     pub enabled_features: Vec<Protocols>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    redis: Option<RedisConfig>,
+    transports: TransportConfig,
+    dedup: Arc<Mutex<Deduplicator>>,
+    port_map: Arc<PortMap>,
 }
 
 impl AcarsHubMessageProcessing {
     #[must_use]
-    pub const fn new(features: Vec<Protocols>) -> Self {
+    pub fn new(
+        features: Vec<Protocols>,
+        stream_buffer_size: usize,
+        redis: Option<RedisConfig>,
+        transports: TransportConfig,
+        dedup_window: Duration,
+        port_map: PortMap,
+    ) -> Self {
+        let (message_tx, _) = broadcast::channel(stream_buffer_size.max(1));
         Self {
             enabled_features: features,
+            message_tx,
+            redis,
+            transports,
+            dedup: Arc::new(Mutex::new(Deduplicator::new(dedup_window))),
+            port_map: Arc::new(port_map),
         }
     }
 
-    pub fn run_listener(&mut self, sender: &UnboundedSender<AcarsVdlm2Message>) {
-        // for each enabled feature, spawn a task
+    /// Returns a clone of the broadcast sender so other subsystems (e.g. the
+    /// webserver's SSE/WebSocket routes) can subscribe to the live message feed.
+    #[must_use]
+    pub fn message_sender(&self) -> broadcast::Sender<FoundMessage> {
+        self.message_tx.clone()
+    }
 
+    pub async fn run_listener(&mut self, sender: &UnboundedSender<FoundMessage>) {
+        if let Some(redis) = self.redis.clone().filter(|r| r.subscribe) {
+            info!("Redis subscribe mode enabled, not binding UDP/TCP sockets");
+            for feature in &self.enabled_features {
+                redis_fanout::start_redis_subscriber(*feature, redis.url.clone(), sender.clone());
+            }
+            return;
+        }
+
+        let publisher = match &self.redis {
+            Some(redis) if redis.publish => match RedisPublisher::new(&redis.url).await {
+                Ok(publisher) => Some(Arc::new(publisher)),
+                Err(e) => {
+                    error!("Failed to set up Redis publisher, continuing without it: {e}");
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        // for each enabled feature, spawn a task using its configured transport
         for feature in &self.enabled_features {
-            start_udp_listener(*feature, sender.clone());
+            match self.transports.get(feature) {
+                Some(Transport::ListenTcp) => {
+                    start_tcp_listen_listener(
+                        *feature,
+                        sender.clone(),
+                        self.message_tx.clone(),
+                        publisher.clone(),
+                        self.dedup.clone(),
+                        self.port_map.clone(),
+                    );
+                }
+                Some(Transport::ConnectTcp(remote_addr)) => {
+                    start_tcp_connect_listener(
+                        *feature,
+                        remote_addr.clone(),
+                        sender.clone(),
+                        self.message_tx.clone(),
+                        publisher.clone(),
+                        self.dedup.clone(),
+                    );
+                }
+                Some(Transport::Udp) | None => {
+                    start_udp_listener(
+                        *feature,
+                        sender.clone(),
+                        self.message_tx.clone(),
+                        publisher.clone(),
+                        self.dedup.clone(),
+                        self.port_map.clone(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Decodes one raw message line (from a UDP datagram or a TCP line) and fans
+/// it out to the live broadcast channel, Redis, and the database listener.
+/// Shared by every transport so they all see identical handling.
+async fn handle_raw_message(
+    feature: Protocols,
+    raw: &str,
+    sender: &UnboundedSender<FoundMessage>,
+    message_tx: &broadcast::Sender<FoundMessage>,
+    redis_publisher: &Option<Arc<RedisPublisher>>,
+    dedup: &Mutex<Deduplicator>,
+) {
+    // ADS-B uses its own parser/record type (see `IngestedMessage::Adsb`),
+    // so it can't go through `acars_vdlm2_parser::DecodeMessage` like every
+    // other feature; everything else still shares one decode path.
+    let ingested_message = if feature == Protocols::Adsb {
+        match DecodeAdsbMessage::decode_message(raw) {
+            Ok(adsb_message) => IngestedMessage::Adsb(adsb_message),
+            Err(e) => {
+                error!("Failed to decode ADS-B message: {e}");
+                return;
+            }
+        }
+    } else {
+        match raw.decode_message() {
+            Ok(json) => IngestedMessage::Acars(json),
+            Err(e) => {
+                error!("Failed to decode message: {e}");
+                return;
+            }
+        }
+    };
+
+    debug!("Received {feature} message: {ingested_message:?}");
+    let found_message = FoundMessage::new(feature, ingested_message);
+
+    let found_message = {
+        let mut dedup = dedup.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        match dedup.check(found_message) {
+            Some(found_message) => found_message,
+            None => {
+                debug!(
+                    "Dropping duplicate {feature} message ({} suppressed so far)",
+                    dedup.suppressed_count()
+                );
+                return;
+            }
         }
+    };
+
+    // the live stream subscribers don't need an ack, and a stalled
+    // browser tab shouldn't be able to stop ingestion, so a send
+    // with no subscribers (or a full ring that already dropped the
+    // slowest reader) is not an error here.
+    let _ = message_tx.send(found_message.clone());
+
+    if let Some(publisher) = redis_publisher {
+        publisher.publish(&found_message).await;
     }
+
+    // send the message to the database listener
+    if let Err(e) = sender.send(found_message) {
+        error!("Failed to send message to database listener: {e}");
+    }
+}
+
+/// Why a raw datagram/line couldn't be decoded, kept distinct so the log
+/// line tells a corrupted transport apart from a merely malformed message.
+#[derive(Debug)]
+enum RawDecodeError {
+    /// The bytes weren't valid UTF-8 at all.
+    InvalidUtf8(str::Utf8Error),
+    /// Valid text, but not a complete, well-formed JSON value.
+    Unparseable(serde_json::Error),
 }
 
-fn start_udp_listener(feature: Protocols, sender: UnboundedSender<AcarsVdlm2Message>) {
+impl std::fmt::Display for RawDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUtf8(e) => write!(f, "not valid UTF-8: {e}"),
+            Self::Unparseable(e) => write!(f, "valid text but unparseable JSON: {e}"),
+        }
+    }
+}
+
+/// Splits `bytes` into however many concatenated JSON values it contains: a
+/// single UDP datagram can carry several VDLM2/HFDL/ACARS messages back to
+/// back with no separator between them. Returns every value that parsed
+/// (re-serialized back to text, since the decoder works on `&str`) alongside
+/// the error for any unparseable bytes left over, so one bad trailing
+/// fragment doesn't cost the messages that parsed ahead of it.
+fn split_messages(bytes: &[u8]) -> (Vec<String>, Option<RawDecodeError>) {
+    let text = match str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => return (Vec::new(), Some(RawDecodeError::InvalidUtf8(e))),
+    };
+
+    let mut messages = Vec::new();
+    let mut stream = serde_json::Deserializer::from_str(text).into_iter::<serde_json::Value>();
+
+    for result in &mut stream {
+        match result {
+            Ok(value) => messages.push(value.to_string()),
+            Err(e) => return (messages, Some(RawDecodeError::Unparseable(e))),
+        }
+    }
+
+    (messages, None)
+}
+
+/// Splits a UDP datagram's raw bytes into its (possibly several) concatenated
+/// JSON messages and decodes/fans out each one. Unlike TCP's newline-delimited
+/// lines, a datagram has no separator between back-to-back messages, so this
+/// can't just defer to [`handle_raw_message`] once per read.
+async fn handle_raw_datagram(
+    feature: Protocols,
+    bytes: &[u8],
+    addr: SocketAddr,
+    sender: &UnboundedSender<FoundMessage>,
+    message_tx: &broadcast::Sender<FoundMessage>,
+    redis_publisher: &Option<Arc<RedisPublisher>>,
+    dedup: &Mutex<Deduplicator>,
+) {
+    let (messages, trailing_error) = split_messages(bytes);
+
+    for raw in &messages {
+        handle_raw_message(feature, raw, sender, message_tx, redis_publisher, dedup).await;
+    }
+
+    if let Some(error) = trailing_error {
+        warn!("Discarding malformed trailing data in {feature} datagram from {addr}: {error}");
+    }
+}
+
+fn start_udp_listener(
+    feature: Protocols,
+    sender: UnboundedSender<FoundMessage>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    redis_publisher: Option<Arc<RedisPublisher>>,
+    dedup: Arc<Mutex<Deduplicator>>,
+    port_map: Arc<PortMap>,
+) {
     tokio::spawn(async move {
         // spawn a UDP Tokio listener
 
-        let port: u32 = feature.to_tcp_udp_port();
+        let port: u32 = port_map.port(feature);
 
         info!("Starting {feature} UDP listener on port {port}");
 
@@ -102,25 +345,22 @@ fn start_udp_listener(feature: Protocols, sender: UnboundedSender<AcarsVdlm2Mess
             }
         };
 
-        let mut buf = [0; 8192];
+        // VDLM2/HFDL JSON payloads (especially ones carrying a populated
+        // `libacars` decode) can run well past a single Ethernet frame; a
+        // too-small buffer silently truncates the datagram instead of
+        // erroring, producing a JSON parse failure downstream that's hard to
+        // tell apart from a genuinely malformed message.
+        let mut buf = [0; UDP_RECV_BUFFER_SIZE];
         loop {
             match socket.recv_from(&mut buf).await {
                 Ok((len, addr)) => {
-                    let message = String::from_utf8_lossy(&buf[..len]); // FIXME: I need to patch the parser to accept bytes and display
-                    // serialize the message to JSON
-                    let json_message = match message.decode_message() {
-                        Ok(json) => json,
-                        Err(e) => {
-                            error!("Failed to decode message: {e}");
-                            continue;
-                        }
-                    };
-
-                    debug!("Received {feature} message from {addr}: {json_message:?}");
-                    // send the message to the database listener
-                    if let Err(e) = sender.send(json_message) {
-                        error!("Failed to send message to database listener: {e}");
+                    if len == buf.len() {
+                        warn!(
+                            "{feature} UDP datagram from {addr} filled the {UDP_RECV_BUFFER_SIZE}-byte receive buffer; it may have been truncated"
+                        );
                     }
+                    debug!("Received {feature} datagram from {addr}");
+                    handle_raw_datagram(feature, &buf[..len], addr, &sender, &message_tx, &redis_publisher, &dedup).await;
                 }
                 Err(e) => {
                     error!("Failed to receive data: {e}");
@@ -130,3 +370,110 @@ fn start_udp_listener(feature: Protocols, sender: UnboundedSender<AcarsVdlm2Mess
         }
     });
 }
+
+fn start_tcp_listen_listener(
+    feature: Protocols,
+    sender: UnboundedSender<FoundMessage>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    redis_publisher: Option<Arc<RedisPublisher>>,
+    dedup: Arc<Mutex<Deduplicator>>,
+    port_map: Arc<PortMap>,
+) {
+    tokio::spawn(async move {
+        let port: u32 = port_map.port(feature);
+
+        info!("Starting {feature} TCP listener on port {port}");
+
+        let listener = match TcpListener::bind(format!("0.0.0.0:{port}")).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind TCP {feature} listener: {e}");
+                return;
+            }
+        };
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    debug!("Accepted {feature} TCP connection from {addr}");
+                    tokio::spawn(read_tcp_lines(
+                        feature,
+                        stream,
+                        sender.clone(),
+                        message_tx.clone(),
+                        redis_publisher.clone(),
+                        dedup.clone(),
+                    ));
+                }
+                Err(e) => error!("Failed to accept {feature} TCP connection: {e}"),
+            }
+        }
+    });
+}
+
+fn start_tcp_connect_listener(
+    feature: Protocols,
+    remote_addr: String,
+    sender: UnboundedSender<FoundMessage>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    redis_publisher: Option<Arc<RedisPublisher>>,
+    dedup: Arc<Mutex<Deduplicator>>,
+) {
+    tokio::spawn(async move {
+        loop {
+            info!("Connecting to {feature} source at {remote_addr}");
+
+            match TcpStream::connect(&remote_addr).await {
+                Ok(stream) => {
+                    read_tcp_lines(
+                        feature,
+                        stream,
+                        sender.clone(),
+                        message_tx.clone(),
+                        redis_publisher.clone(),
+                        dedup.clone(),
+                    )
+                    .await;
+                    warn!("{feature} source at {remote_addr} disconnected, reconnecting in {RECONNECT_BACKOFF:?}");
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to connect to {feature} source at {remote_addr}: {e}, retrying in {RECONNECT_BACKOFF:?}"
+                    );
+                }
+            }
+
+            sleep(RECONNECT_BACKOFF).await;
+        }
+    });
+}
+
+/// Reads newline-delimited JSON messages off `stream` until it's closed or
+/// errors, handling partial reads across packet boundaries via a buffered
+/// line reader.
+async fn read_tcp_lines(
+    feature: Protocols,
+    stream: TcpStream,
+    sender: UnboundedSender<FoundMessage>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    redis_publisher: Option<Arc<RedisPublisher>>,
+    dedup: Arc<Mutex<Deduplicator>>,
+) {
+    let mut lines = BufReader::new(stream).lines();
+
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                handle_raw_message(feature, &line, &sender, &message_tx, &redis_publisher, &dedup).await;
+            }
+            Ok(None) => break,
+            Err(e) => {
+                error!("Error reading {feature} TCP stream: {e}");
+                break;
+            }
+        }
+    }
+}
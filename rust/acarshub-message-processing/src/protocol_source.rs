@@ -0,0 +1,222 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A minimal, self-contained input connector: [`ProtocolSource::spawn`] opens
+//! a TCP or UDP stream for a single [`Protocols`] value and hands back a
+//! plain `mpsc::Receiver<FoundMessage>`, independent of [`crate::AcarsHubMessageProcessing`]'s
+//! broadcast/Redis/db-listener fan-out. The TCP transport is built on
+//! `sdre_stubborn_io` so a decoder container (acarsdec/dumpvdl2/etc.)
+//! restarting doesn't permanently kill the feed: the underlying socket
+//! reconnects with exponential backoff transparently underneath the line
+//! reader, so a partial line already buffered survives the gap.
+
+use std::time::Duration;
+
+use acars_vdlm2_parser::DecodeMessage;
+use acarshub_common::{FoundMessage, IngestedMessage};
+use sdre_stubborn_io::{ReconnectOptions, StubbornTcpStream};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::{PortMap, Protocols};
+
+/// Capacity of the channel [`ProtocolSource::spawn`] hands back.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// How a [`ProtocolSource`] reaches its upstream decoder container.
+#[derive(Debug, Clone, Copy)]
+pub enum SourceTransport {
+    /// Dial out to `host:port` with auto-reconnect; pairs with a decoder
+    /// container exposing a TCP output.
+    Tcp,
+    /// Bind `host:port` and receive datagrams, same as this crate's existing
+    /// UDP listener (UDP has no connection to lose, so there's nothing to
+    /// reconnect).
+    Udp,
+}
+
+/// An input connector for a single protocol, decoupled from this crate's own
+/// ingestion pipeline. See the module docs for why it exists alongside
+/// [`crate::AcarsHubMessageProcessing::run_listener`] rather than replacing it.
+pub struct ProtocolSource;
+
+impl ProtocolSource {
+    /// Spawns a task that opens `addr` (defaulting to
+    /// `127.0.0.1:{port_map.port(protocol)}`) via `transport`, decodes every
+    /// message it sees, tags it via [`FoundMessage::new`] (content-based
+    /// detection, with `protocol` as the fallback hint), and forwards it on
+    /// the returned channel.
+    #[must_use]
+    pub fn spawn(
+        protocol: Protocols,
+        addr: Option<String>,
+        transport: SourceTransport,
+        port_map: &PortMap,
+    ) -> Receiver<FoundMessage> {
+        let addr = addr.unwrap_or_else(|| format!("127.0.0.1:{}", port_map.port(protocol)));
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        match transport {
+            SourceTransport::Tcp => {
+                tokio::spawn(run_tcp_source(protocol, addr, tx));
+            }
+            SourceTransport::Udp => {
+                tokio::spawn(run_udp_source(protocol, addr, tx));
+            }
+        }
+
+        rx
+    }
+}
+
+/// Yields an unbounded sequence of reconnect delays, doubling from 1s up to
+/// a 64s ceiling so a long outage doesn't end up retrying every second.
+fn backoff_schedule() -> impl Iterator<Item = Duration> {
+    (0u32..).map(|attempt| Duration::from_secs(2u64.saturating_pow(attempt.min(6))))
+}
+
+async fn run_tcp_source(protocol: Protocols, addr: String, tx: Sender<FoundMessage>) {
+    let logged_addr = addr.clone();
+    let options = ReconnectOptions::new()
+        .with_exit_if_first_connect_fails(false)
+        .with_retries_generator(backoff_schedule)
+        .with_on_disconnect_callback(move || {
+            warn!("ProtocolSource for {protocol} lost its connection to {logged_addr}, reconnecting");
+        });
+
+    let stream = match StubbornTcpStream::connect_with_options(&addr, options).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("ProtocolSource for {protocol} failed to connect to {addr}: {e}");
+            return;
+        }
+    };
+
+    // The `BufReader`, not the socket, owns any partially-read line, so a
+    // `StubbornIo` reconnect underneath it can't drop a message straddling
+    // the gap -- the buffered half just gets its other half once the
+    // connection resumes.
+    let mut lines = BufReader::new(stream).lines();
+
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                if !line.trim().is_empty() {
+                    forward_raw(protocol, &line, &tx).await;
+                }
+            }
+            Ok(None) => {
+                info!("ProtocolSource for {protocol} at {addr} closed permanently");
+                break;
+            }
+            Err(e) => {
+                error!("ProtocolSource for {protocol} at {addr} stream error: {e}");
+                break;
+            }
+        }
+    }
+}
+
+async fn run_udp_source(protocol: Protocols, addr: String, tx: Sender<FoundMessage>) {
+    let socket = match UdpSocket::bind(&addr).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            error!("ProtocolSource for {protocol} failed to bind UDP {addr}: {e}");
+            return;
+        }
+    };
+
+    let mut buf = [0; crate::UDP_RECV_BUFFER_SIZE];
+    loop {
+        match socket.recv_from(&mut buf).await {
+            Ok((len, peer)) => {
+                let (messages, trailing_error) = crate::split_messages(&buf[..len]);
+
+                for raw in &messages {
+                    forward_raw(protocol, raw, &tx).await;
+                }
+
+                if let Some(error) = trailing_error {
+                    warn!("ProtocolSource for {protocol} discarding malformed trailing data from {peer}: {error}");
+                }
+            }
+            Err(e) => {
+                error!("ProtocolSource for {protocol} UDP recv error: {e}");
+                break;
+            }
+        }
+    }
+}
+
+async fn forward_raw(protocol: Protocols, raw: &str, tx: &Sender<FoundMessage>) {
+    match raw.decode_message() {
+        Ok(message) => {
+            if tx.send(FoundMessage::new(protocol, IngestedMessage::Acars(message))).await.is_err() {
+                debug!("ProtocolSource receiver for {protocol} dropped, discarding message");
+            }
+        }
+        Err(e) => error!("ProtocolSource for {protocol} failed to decode message: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+
+    use super::{FoundMessage, PortMap, ProtocolSource, Protocols, SourceTransport};
+
+    /// Shaped like a single line of acarsdec's ACARS JSON output.
+    const SAMPLE_ACARS_LINE: &str = r#"{"timestamp":1700000000.0,"station_id":"KTEST","channel":1,"freq":131.550,"level":-10.5,"error":0,"mode":"2","label":"H1","block_id":"2","ack":"!","msgno":"M01A","flight":"UAL123","tail":"N12345","text":"TEST MESSAGE","is-response":false,"is-onground":false}"#;
+
+    /// `ProtocolSource::spawn` is exported but nothing in this tree calls it
+    /// (see the chunk2-1 review comment), so nothing would notice if an
+    /// unrelated change (e.g. to `FoundMessage::new`'s signature) silently
+    /// broke it. This pins the one behavior that matters: a line written to
+    /// the connection it dials comes out the other end as a `FoundMessage`
+    /// tagged with the protocol it was spawned for.
+    #[tokio::test]
+    async fn spawn_tcp_round_trips_a_message() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test listener");
+        let addr = listener.local_addr().expect("failed to read test listener addr");
+
+        let mut rx = ProtocolSource::spawn(
+            Protocols::Acars,
+            Some(addr.to_string()),
+            SourceTransport::Tcp,
+            &PortMap::default(),
+        );
+
+        let (mut socket, _) = listener.accept().await.expect("test peer never connected");
+        socket
+            .write_all(format!("{SAMPLE_ACARS_LINE}\n").as_bytes())
+            .await
+            .expect("failed to write test message");
+
+        let found: FoundMessage = timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for a message")
+            .expect("channel closed without a message");
+
+        assert_eq!(found.protocol, Protocols::Acars);
+    }
+}
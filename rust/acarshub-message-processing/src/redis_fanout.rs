@@ -0,0 +1,124 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional Redis pub/sub fan-out of decoded messages, for multi-instance
+//! deployments: a "publish" node broadcasts every `FoundMessage` it decodes,
+//! and a "subscribe" node consumes those channels instead of binding UDP
+//! sockets of its own.
+
+use std::time::Duration;
+
+use acarshub_common::{FoundMessage, Protocols};
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use tokio::sync::mpsc::UnboundedSender;
+
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+fn redis_channel(protocol: Protocols) -> String {
+    format!("acarshub.{}", protocol.tag())
+}
+
+/// Publishes decoded messages to Redis so sibling instances / external
+/// subscribers can consume them. Built on `ConnectionManager`, which already
+/// reconnects with backoff on its own, so a dropped Redis connection never
+/// takes down message ingestion.
+pub struct RedisPublisher {
+    connection: redis::aio::ConnectionManager,
+}
+
+impl RedisPublisher {
+    /// Connects to `redis_url`. Fails if the URL can't be parsed or the
+    /// initial connection can't be established; once connected, the
+    /// `ConnectionManager` reconnects on its own.
+    pub async fn new(redis_url: &str) -> anyhow::Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let connection = client.get_connection_manager().await?;
+        Ok(Self { connection })
+    }
+
+    pub async fn publish(&self, found: &FoundMessage) {
+        let channel = redis_channel(found.protocol);
+        let payload = match serde_json::to_string(found) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize message for Redis publish: {e}");
+                return;
+            }
+        };
+
+        let mut connection = self.connection.clone();
+        if let Err(e) = connection.publish::<_, _, ()>(&channel, payload).await {
+            warn!("Failed to publish message to Redis channel {channel}: {e}");
+        }
+    }
+}
+
+/// Runs a `--redis-subscribe` listener for `protocol`: subscribes to its
+/// Redis channel and forwards every message it receives into `sender` (the
+/// database listener). These messages go to the database only: they are not
+/// de-duplicated and not sent on the live-stream broadcast. Reconnects with
+/// a fixed backoff if the Redis connection drops.
+pub fn start_redis_subscriber(protocol: Protocols, redis_url: String, sender: UnboundedSender<FoundMessage>) {
+    tokio::spawn(async move {
+        let channel = redis_channel(protocol);
+
+        loop {
+            if let Err(e) = subscribe_once(&redis_url, &channel, &sender).await {
+                warn!(
+                    "Redis subscriber for {channel} disconnected ({e}), retrying in {}s",
+                    RECONNECT_BACKOFF.as_secs()
+                );
+            }
+
+            tokio::time::sleep(RECONNECT_BACKOFF).await;
+        }
+    });
+}
+
+async fn subscribe_once(
+    redis_url: &str,
+    channel: &str,
+    sender: &UnboundedSender<FoundMessage>,
+) -> anyhow::Result<()> {
+    let client = redis::Client::open(redis_url)?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(channel).await?;
+
+    info!("Subscribed to Redis channel {channel}");
+
+    let mut stream = pubsub.on_message();
+    while let Some(message) = stream.next().await {
+        let payload: String = match message.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Malformed Redis payload on {channel}: {e}");
+                continue;
+            }
+        };
+
+        match serde_json::from_str::<FoundMessage>(&payload) {
+            Ok(found_message) => {
+                if let Err(e) = sender.send(found_message) {
+                    error!("Failed to send Redis-sourced message to database listener: {e}");
+                }
+            }
+            Err(e) => error!("Failed to decode Redis message on {channel}: {e}"),
+        }
+    }
+
+    anyhow::bail!("Redis pub/sub stream for {channel} ended")
+}
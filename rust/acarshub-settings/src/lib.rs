@@ -29,7 +29,12 @@
 pub extern crate clap as clap;
 use tracing::Level;
 
-use acarshub_message_processing::Protocols;
+#[macro_use]
+extern crate tracing;
+
+use std::time::Duration;
+
+use acarshub_message_processing::{PortMap, Protocols, RedisConfig, Transport, TransportConfig};
 use clap::Parser;
 
 #[allow(clippy::struct_excessive_bools)]
@@ -68,6 +73,214 @@ pub struct Input {
 
     #[clap(long, env = "ENABLE_IRDM", value_parser, default_value = "false")]
     pub enable_irdm: bool,
+
+    #[clap(long, env = "ENABLE_ADSB", value_parser, default_value = "false")]
+    pub enable_adsb: bool,
+
+    #[clap(
+        long,
+        env = "STREAM_BUFFER_SIZE",
+        value_parser,
+        default_value = "1024",
+        help = "Number of messages buffered per live-stream subscriber (SSE/WebSocket) before the slowest client is considered lagged"
+    )]
+    pub stream_buffer_size: usize,
+
+    #[clap(
+        long,
+        env = "REDIS_URL",
+        value_parser,
+        help = "Redis connection URL used for --redis-publish / --redis-subscribe, e.g. redis://127.0.0.1:6379"
+    )]
+    pub redis_url: Option<String>,
+
+    #[clap(
+        long,
+        env = "REDIS_PUBLISH",
+        value_parser,
+        default_value = "false",
+        help = "Publish every decoded message to Redis (per-protocol channels) in addition to storing it locally"
+    )]
+    pub redis_publish: bool,
+
+    #[clap(
+        long,
+        env = "REDIS_SUBSCRIBE",
+        value_parser,
+        default_value = "false",
+        help = "Consume decoded messages from Redis instead of binding UDP listeners; pairs with a --redis-publish instance elsewhere"
+    )]
+    pub redis_subscribe: bool,
+
+    #[clap(
+        long,
+        env = "COMPRESSION_THRESHOLD",
+        value_parser,
+        default_value = "128",
+        help = "Minimum size in bytes for msg_text/libacars before it is zstd-compressed on insert"
+    )]
+    pub compression_threshold: usize,
+
+    #[clap(
+        long,
+        env = "COMPRESSION_LEVEL",
+        value_parser,
+        default_value = "3",
+        help = "zstd compression level used for msg_text/libacars"
+    )]
+    pub compression_level: i32,
+
+    #[clap(
+        long,
+        env = "DB_POOL_SIZE",
+        value_parser,
+        default_value = "8",
+        help = "Number of pooled SQLite connections used for database reads and writes"
+    )]
+    pub db_pool_size: u32,
+
+    #[clap(
+        long,
+        env = "DB_BUSY_TIMEOUT_MS",
+        value_parser,
+        default_value = "5000",
+        help = "busy_timeout (in milliseconds) applied to every pooled SQLite connection, so concurrent writers wait out SQLITE_BUSY instead of failing"
+    )]
+    pub db_busy_timeout_ms: u32,
+
+    #[clap(
+        long,
+        env = "DB_BATCH_SIZE",
+        value_parser,
+        default_value = "100",
+        help = "Maximum number of messages grouped into a single insert transaction"
+    )]
+    pub db_batch_size: usize,
+
+    #[clap(
+        long,
+        env = "DB_FLUSH_INTERVAL_MS",
+        value_parser,
+        default_value = "500",
+        help = "Maximum time in milliseconds a partial batch waits for more messages before it's flushed anyway"
+    )]
+    pub db_flush_interval_ms: u64,
+
+    #[clap(
+        long,
+        env = "TCP_LISTEN_PROTOCOLS",
+        value_delimiter = ',',
+        help = "Comma-separated protocol tags (acars,vdlm,hfdl,imsl,irdm,adsb) to ingest via an inbound TCP listener (newline-delimited JSON) instead of UDP"
+    )]
+    pub tcp_listen_protocols: Vec<String>,
+
+    #[clap(
+        long,
+        env = "TCP_CONNECT",
+        value_delimiter = ',',
+        help = "Comma-separated PROTOCOL=HOST:PORT pairs to ingest via an outbound TCP connection (newline-delimited JSON) instead of UDP, e.g. vdlm=127.0.0.1:5555"
+    )]
+    pub tcp_connect: Vec<String>,
+
+    #[clap(
+        long,
+        env = "JWT_SECRET",
+        value_parser,
+        default_value = "",
+        help = "Secret used to sign/verify login JWTs, to a long random value. Required: acarshub refuses to start without it"
+    )]
+    pub jwt_secret: String,
+
+    #[clap(
+        long,
+        env = "JWT_TOKEN_TTL_SECONDS",
+        value_parser,
+        default_value = "3600",
+        help = "How long an issued JWT remains valid before it must be refreshed via another login"
+    )]
+    pub jwt_token_ttl_seconds: i64,
+
+    #[clap(
+        long,
+        env = "ALLOW_ANONYMOUS_READ",
+        value_parser,
+        default_value = "true",
+        help = "Allow GET/HEAD requests without a token; mutating requests always require one"
+    )]
+    pub allow_anonymous_read: bool,
+
+    #[clap(
+        long,
+        env = "BOOTSTRAP_ADMIN_USERNAME",
+        help = "Create this admin user at startup if the users table is empty; requires --bootstrap-admin-password"
+    )]
+    pub bootstrap_admin_username: Option<String>,
+
+    #[clap(
+        long,
+        env = "BOOTSTRAP_ADMIN_PASSWORD",
+        help = "Password for --bootstrap-admin-username; only used the first time (ignored once any user account exists)"
+    )]
+    pub bootstrap_admin_password: Option<String>,
+
+    #[clap(
+        long,
+        env = "ALERT_TERMS",
+        value_delimiter = ',',
+        help = "Comma-separated terms to watch for in incoming message text; a match is recorded in alert_stats and delivered to any configured alert sink"
+    )]
+    pub alert_terms: Vec<String>,
+
+    #[clap(
+        long,
+        env = "ALERT_WEBHOOK_URL",
+        value_parser,
+        help = "If set, POST a JSON AlertMatch payload to this URL whenever a watched term matches"
+    )]
+    pub alert_webhook_url: Option<String>,
+
+    #[clap(
+        long,
+        env = "ALERT_MQTT_BROKER",
+        value_parser,
+        help = "If set, publish a JSON AlertMatch payload to --alert-mqtt-topic on this MQTT broker (HOST:PORT) whenever a watched term matches"
+    )]
+    pub alert_mqtt_broker: Option<String>,
+
+    #[clap(
+        long,
+        env = "ALERT_MQTT_TOPIC",
+        value_parser,
+        default_value = "acarshub/alerts",
+        help = "MQTT topic published to when --alert-mqtt-broker is set"
+    )]
+    pub alert_mqtt_topic: String,
+
+    #[clap(
+        long,
+        env = "ALERT_QUEUE_SIZE",
+        value_parser,
+        default_value = "256",
+        help = "Number of AlertMatch events buffered per sink before the slowest one starts dropping the oldest"
+    )]
+    pub alert_queue_size: usize,
+
+    #[clap(
+        long,
+        env = "DEDUP_WINDOW_MS",
+        value_parser,
+        default_value = "2000",
+        help = "How long (in milliseconds) a decoded message is remembered so a repeat heard by another receiver/protocol within the window is treated as a duplicate"
+    )]
+    pub dedup_window_ms: u64,
+
+    #[clap(
+        long,
+        env = "PORT_MAP",
+        value_delimiter = ',',
+        help = "Comma-separated PROTOCOL=PORT overrides of the default UDP/TCP listen ports, e.g. vdlm=15555; useful when running multiple decoder instances or behind acars_router"
+    )]
+    pub port_map: Vec<String>,
 }
 
 impl Input {
@@ -89,9 +302,87 @@ impl Input {
         if self.enable_irdm {
             enabled_features.push(Protocols::Irdm);
         }
+        if self.enable_adsb {
+            enabled_features.push(Protocols::Adsb);
+        }
         enabled_features
     }
 
+    /// Builds the Redis fan-out configuration from the `--redis-*` flags, or
+    /// `None` if neither publish nor subscribe mode was requested.
+    #[must_use]
+    pub fn redis_config(&self) -> Option<RedisConfig> {
+        if !self.redis_publish && !self.redis_subscribe {
+            return None;
+        }
+
+        self.redis_url.clone().map(|url| RedisConfig {
+            url,
+            publish: self.redis_publish,
+            subscribe: self.redis_subscribe,
+        })
+    }
+
+    /// Builds the per-protocol transport overrides from `--tcp-listen-protocols`
+    /// / `--tcp-connect`; a protocol mentioned in neither falls back to UDP.
+    #[must_use]
+    pub fn transport_config(&self) -> TransportConfig {
+        let mut transports = TransportConfig::new();
+
+        for tag in &self.tcp_listen_protocols {
+            match Protocols::from_tag(tag.trim()) {
+                Some(protocol) => {
+                    transports.insert(protocol, Transport::ListenTcp);
+                }
+                None => warn!("Ignoring unknown protocol {tag:?} in --tcp-listen-protocols"),
+            }
+        }
+
+        for entry in &self.tcp_connect {
+            let Some((tag, addr)) = entry.split_once('=') else {
+                warn!("Ignoring malformed --tcp-connect entry {entry:?}, expected PROTOCOL=HOST:PORT");
+                continue;
+            };
+
+            match Protocols::from_tag(tag.trim()) {
+                Some(protocol) => {
+                    transports.insert(protocol, Transport::ConnectTcp(addr.trim().to_string()));
+                }
+                None => warn!("Ignoring unknown protocol {tag:?} in --tcp-connect"),
+            }
+        }
+
+        transports
+    }
+
+    /// Builds the per-protocol port map from the `--port-map` overrides,
+    /// seeded with `PortMap::default`'s ports for anything not overridden.
+    #[must_use]
+    pub fn port_map(&self) -> PortMap {
+        let mut port_map = PortMap::default();
+
+        for entry in &self.port_map {
+            let Some((tag, port)) = entry.split_once('=') else {
+                warn!("Ignoring malformed --port-map entry {entry:?}, expected PROTOCOL=PORT");
+                continue;
+            };
+
+            match (Protocols::from_tag(tag.trim()), port.trim().parse::<u32>()) {
+                (Some(protocol), Ok(port)) => port_map.set(protocol, port),
+                (None, _) => warn!("Ignoring unknown protocol {tag:?} in --port-map"),
+                (_, Err(e)) => warn!("Ignoring malformed port {port:?} in --port-map entry {entry:?}: {e}"),
+            }
+        }
+
+        port_map
+    }
+
+    /// Builds the de-duplication window from the `--dedup-window-ms` flag.
+    #[must_use]
+    pub const fn dedup_config(&self) -> Duration {
+        Duration::from_millis(self.dedup_window_ms)
+    }
+
     #[must_use]
     pub const fn log_level(&self) -> Level {
         match self.log_level {
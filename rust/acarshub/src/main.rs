@@ -28,8 +28,8 @@
 #[macro_use]
 extern crate tracing;
 
-use parking_lot::FairMutex;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::unbounded_channel;
 use tracing_subscriber::{
     EnvFilter,
@@ -41,7 +41,7 @@ use tracing_subscriber::{
 use acarshub_database::{AcarsHubDatabase, db_listener::DatabaseListener};
 use acarshub_message_processing::AcarsHubMessageProcessing;
 use acarshub_settings::{Input, clap::Parser};
-use acarshub_webserver::AcarsHubWebServer;
+use acarshub_webserver::{AcarsHubWebServer, AuthConfig};
 
 #[tokio::main]
 async fn main() {
@@ -65,16 +65,69 @@ async fn main() {
         input.log_level().as_str()
     );
 
-    let database = match AcarsHubDatabase::new(&input.database) {
-        Ok(db) => Arc::new(FairMutex::new(db)),
+    let database = match AcarsHubDatabase::new(
+        &input.database,
+        input.db_pool_size,
+        input.db_busy_timeout_ms,
+        input.compression_threshold,
+        input.compression_level,
+    ) {
+        Ok(db) => Arc::new(db),
         Err(_e) => {
             error!("Error creating db. Exiting");
             std::process::exit(69);
         }
     };
 
+    if let Some(username) = &input.bootstrap_admin_username {
+        match database.has_no_users() {
+            Ok(true) => {
+                let Some(password) = &input.bootstrap_admin_password else {
+                    error!("--bootstrap-admin-username was set without --bootstrap-admin-password. Exiting");
+                    std::process::exit(1);
+                };
+
+                #[allow(clippy::cast_possible_wrap)]
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64;
+
+                match database.create_user(username, password, true, now) {
+                    Ok(_) => info!("Bootstrapped admin user {username}"),
+                    Err(e) => error!("Failed to bootstrap admin user {username}: {e}"),
+                }
+            }
+            Ok(false) => debug!("Users already exist, ignoring --bootstrap-admin-username"),
+            Err(e) => error!("Failed to check for existing users: {e}"),
+        }
+    }
+
+    // build the alert sinks (webhook/MQTT) this instance delivers watched-term matches to
+    let mut alert_sinks = Vec::new();
+    if let Some(url) = input.alert_webhook_url.clone() {
+        alert_sinks.push(acarshub_database::alerts::AlertSinkConfig::Webhook { url });
+    }
+    if let Some(broker) = &input.alert_mqtt_broker {
+        match broker.rsplit_once(':').and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?))) {
+            Some((host, port)) => alert_sinks.push(acarshub_database::alerts::AlertSinkConfig::Mqtt {
+                host: host.to_string(),
+                port,
+                topic: input.alert_mqtt_topic.clone(),
+            }),
+            None => error!("Ignoring malformed --alert-mqtt-broker {broker:?}, expected HOST:PORT"),
+        }
+    }
+    let alert_tx = acarshub_database::alerts::start_alert_sinks(alert_sinks, input.alert_queue_size);
+
     // create the database listener
-    let db_listener = DatabaseListener::new(database.clone());
+    let db_listener = DatabaseListener::new(
+        database.clone(),
+        input.db_batch_size,
+        Duration::from_millis(input.db_flush_interval_ms),
+        input.alert_terms.clone(),
+        alert_tx,
+    );
     // create the channel for the database listener
     let (sender, receiver) = unbounded_channel();
     // start the database listener
@@ -88,11 +141,35 @@ async fn main() {
     }
 
     // create the message processing object
-    let mut message_processing = AcarsHubMessageProcessing::new(protocols);
+    let mut message_processing = AcarsHubMessageProcessing::new(
+        protocols,
+        input.stream_buffer_size,
+        input.redis_config(),
+        input.transport_config(),
+        input.dedup_config(),
+        input.port_map(),
+    );
+    // subscribe the webserver's live feed to the same broadcast this creates
+    let message_tx = message_processing.message_sender();
     // run the message processing
-    message_processing.run_listener(&sender);
+    message_processing.run_listener(&sender).await;
+
+    if input.jwt_secret.is_empty() {
+        error!(
+            "--jwt-secret is not set. HS256 with an empty key is a known, guessable signing \
+             key, not \"no authentication\" -- anyone could mint their own valid tokens. Set \
+             JWT_SECRET to a long random value before starting. Exiting"
+        );
+        std::process::exit(1);
+    }
+
+    let auth_config = AuthConfig {
+        jwt_secret: input.jwt_secret.clone(),
+        token_ttl_seconds: input.jwt_token_ttl_seconds,
+        allow_anonymous_read: input.allow_anonymous_read,
+    };
 
-    let mut webserver = AcarsHubWebServer::new(database);
+    let mut webserver = AcarsHubWebServer::new(database, message_tx, auth_config);
 
     // run the web server
     webserver.run().await.expect("Error running web server");
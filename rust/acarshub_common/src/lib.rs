@@ -26,17 +26,21 @@
 )]
 // #![warn(missing_docs)]
 
+use std::collections::HashMap;
 use std::fmt;
 
 use acars_vdlm2_parser::AcarsVdlm2Message;
+use sdre_rust_adsb_parser::AdsbMessage;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Protocols {
     Acars,
     Vdlm,
     Hfdl,
     Imsl,
     Irdm,
+    Adsb,
 }
 
 impl fmt::Display for Protocols {
@@ -47,6 +51,7 @@ impl fmt::Display for Protocols {
             Self::Hfdl => write!(f, "HFDL"),
             Self::Imsl => write!(f, "Inmarsat L-Band"),
             Self::Irdm => write!(f, "Iridium"),
+            Self::Adsb => write!(f, "ADS-B"),
         }
     }
 }
@@ -60,11 +65,202 @@ impl Protocols {
             Self::Hfdl => 5556,
             Self::Imsl => 5557,
             Self::Irdm => 5558,
+            // readsb/tar1090's `--net-json-port` default, kept distinct from
+            // the beast (30005) and SBS (30003) outputs those tools also expose.
+            Self::Adsb => 30_047,
+        }
+    }
+
+    /// A stable, lowercase identifier for this protocol, suitable for use in
+    /// channel names, query-string filters, and wire formats where the
+    /// human-readable `Display` text (with spaces/hyphens) would be awkward.
+    #[must_use]
+    pub const fn tag(self) -> &'static str {
+        match self {
+            Self::Acars => "acars",
+            Self::Vdlm => "vdlm",
+            Self::Hfdl => "hfdl",
+            Self::Imsl => "imsl",
+            Self::Irdm => "irdm",
+            Self::Adsb => "adsb",
+        }
+    }
+
+    /// Reverses [`Self::tag`], e.g. for parsing protocol names out of CLI flags.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "acars" => Some(Self::Acars),
+            "vdlm" => Some(Self::Vdlm),
+            "hfdl" => Some(Self::Hfdl),
+            "imsl" => Some(Self::Imsl),
+            "irdm" => Some(Self::Irdm),
+            "adsb" => Some(Self::Adsb),
+            _ => None,
+        }
+    }
+
+    /// Reverses [`Self::to_tcp_udp_port`], e.g. for an input multiplexer
+    /// tagging a byte stream by the port it arrived on. `None` if `port`
+    /// isn't one of `to_tcp_udp_port`'s default ports; overrides held in a
+    /// [`PortMap`] are not consulted.
+    #[must_use]
+    pub const fn from_tcp_udp_port(port: u32) -> Option<Self> {
+        match port {
+            5550 => Some(Self::Acars),
+            5555 => Some(Self::Vdlm),
+            5556 => Some(Self::Hfdl),
+            5557 => Some(Self::Imsl),
+            5558 => Some(Self::Irdm),
+            30_047 => Some(Self::Adsb),
+            _ => None,
+        }
+    }
+
+    /// Infers the protocol from which variant `acars_vdlm2_parser` matched
+    /// while decoding a message, so a merged feed (e.g. an `acars_router` mux
+    /// combining several decoders onto one stream) can be tagged correctly
+    /// with no port separation to fall back on. `acars_vdlm2_parser` already
+    /// discriminates on the message's own fields to pick a variant, so this
+    /// is never actually ambiguous today; it returns `Option` so a caller
+    /// can still fall back to a port-derived hint via [`FoundMessage::new`]
+    /// if a future parser version ever produces an untagged variant.
+    #[must_use]
+    pub const fn detect(message: &AcarsVdlm2Message) -> Option<Self> {
+        match message {
+            AcarsVdlm2Message::AcarsMessage(_) => Some(Self::Acars),
+            AcarsVdlm2Message::Vdlm2Message(_) => Some(Self::Vdlm),
+            AcarsVdlm2Message::HfdlMessage(_) => Some(Self::Hfdl),
+            AcarsVdlm2Message::ImslMessage(_) => Some(Self::Imsl),
+            AcarsVdlm2Message::IrdmMessage(_) => Some(Self::Irdm),
+        }
+    }
+}
+
+/// Per-protocol listen/dial port, seeded from [`Protocols::to_tcp_udp_port`]
+/// but overridable at runtime -- operators commonly remap these when running
+/// multiple decoder instances or behind `acars_router`. Source-connector code
+/// should consult a `&PortMap` instead of calling `to_tcp_udp_port` directly,
+/// so a deployment-specific override actually takes effect.
+#[derive(Debug, Clone)]
+pub struct PortMap(HashMap<Protocols, u32>);
+
+impl Default for PortMap {
+    /// Seeds every protocol with [`Protocols::to_tcp_udp_port`]'s default.
+    fn default() -> Self {
+        Self(
+            [
+                Protocols::Acars,
+                Protocols::Vdlm,
+                Protocols::Hfdl,
+                Protocols::Imsl,
+                Protocols::Irdm,
+                Protocols::Adsb,
+            ]
+            .into_iter()
+            .map(|protocol| (protocol, protocol.to_tcp_udp_port()))
+            .collect(),
+        )
+    }
+}
+
+impl PortMap {
+    /// The port `protocol` listens/dials on, falling back to
+    /// [`Protocols::to_tcp_udp_port`] if `protocol` has no entry (e.g. a
+    /// `PortMap` built by hand rather than via [`Self::default`]).
+    #[must_use]
+    pub fn port(&self, protocol: Protocols) -> u32 {
+        self.0.get(&protocol).copied().unwrap_or_else(|| protocol.to_tcp_udp_port())
+    }
+
+    /// Overrides `protocol`'s port, replacing whatever it was seeded with.
+    pub fn set(&mut self, protocol: Protocols, port: u32) {
+        self.0.insert(protocol, port);
+    }
+}
+
+impl Serialize for Protocols {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.tag())
+    }
+}
+
+impl<'de> Deserialize<'de> for Protocols {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tag = String::deserialize(deserializer)?;
+        Self::from_tag(&tag).ok_or_else(|| de::Error::custom(format!("unknown protocol: {tag}")))
+    }
+}
+
+/// Everything this crate can ingest in one pipeline: ACARS/VDLM2/HFDL/Inmarsat/
+/// Iridium messages (via `acars_vdlm2_parser`) alongside ADS-B position/identity
+/// records (via the org's `sdre-rust-adsb-parser`), so a single `FoundMessage`
+/// stream can correlate ACARS traffic with live aircraft data from the same
+/// airframe instead of running two unrelated pipelines side by side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IngestedMessage {
+    Acars(AcarsVdlm2Message),
+    Adsb(AdsbMessage),
+}
+
+impl IngestedMessage {
+    /// The airframe's registration/tail number, if this message carries one --
+    /// `tail` for ACARS, `r` (registration) for ADS-B.
+    #[must_use]
+    pub fn registration(&self) -> Option<String> {
+        match self {
+            Self::Acars(AcarsVdlm2Message::AcarsMessage(msg)) => msg.tail.clone(),
+            Self::Acars(_) => None,
+            Self::Adsb(msg) => msg.registration.clone(),
+        }
+    }
+
+    /// The airframe's ICAO 24-bit address, as hex text.
+    #[must_use]
+    pub fn icao_hex(&self) -> Option<String> {
+        match self {
+            Self::Acars(AcarsVdlm2Message::AcarsMessage(msg)) => msg.icao.clone().map(|icao| icao.to_string()),
+            Self::Acars(_) => None,
+            Self::Adsb(msg) => Some(msg.icao_address.clone()),
+        }
+    }
+
+    /// The flight/callsign identifier, if this message carries one.
+    #[must_use]
+    pub fn callsign(&self) -> Option<String> {
+        match self {
+            Self::Acars(AcarsVdlm2Message::AcarsMessage(msg)) => msg.flight.clone(),
+            Self::Acars(_) => None,
+            Self::Adsb(msg) => msg.flight.clone(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FoundMessage {
     pub protocol: Protocols,
-    pub message: AcarsVdlm2Message,
+    pub message: IngestedMessage,
+}
+
+impl FoundMessage {
+    /// Tags `message` with its protocol: [`Protocols::detect`]'s inference from
+    /// the parsed fields for an ACARS-family message (falling back to
+    /// `port_hint`, typically the port or transport the bytes arrived on, when
+    /// detection doesn't resolve), or always [`Protocols::Adsb`] for an ADS-B
+    /// record, since that feed isn't multiplexed with the others.
+    #[must_use]
+    pub fn new(port_hint: Protocols, message: IngestedMessage) -> Self {
+        let protocol = match &message {
+            IngestedMessage::Acars(acars_message) => Protocols::detect(acars_message).unwrap_or(port_hint),
+            IngestedMessage::Adsb(_) => Protocols::Adsb,
+        };
+
+        Self { protocol, message }
+    }
 }
@@ -0,0 +1,86 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mutating read-write API routes gated behind [`crate::auth::require_auth`]:
+//! starring a message and maintaining the alert-ignore list.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SaveMessageRequest {
+    #[serde(default)]
+    term: String,
+    #[serde(default)]
+    type_of_match: String,
+}
+
+pub async fn save_message_handler(
+    State(state): State<AppState>,
+    Path(message_id): Path<i32>,
+    Json(req): Json<SaveMessageRequest>,
+) -> StatusCode {
+    match state.database.save_message(message_id, &req.term, &req.type_of_match) {
+        Ok(()) => StatusCode::CREATED,
+        Err(e) => {
+            error!("Failed to save message {message_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddIgnoreAlertTermRequest {
+    term: String,
+}
+
+pub async fn add_ignore_alert_term_handler(
+    State(state): State<AppState>,
+    Json(req): Json<AddIgnoreAlertTermRequest>,
+) -> StatusCode {
+    match state.database.add_ignore_alert_term(&req.term) {
+        Ok(()) => StatusCode::CREATED,
+        Err(e) => {
+            error!("Failed to add ignore alert term: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub async fn remove_ignore_alert_term_handler(State(state): State<AppState>, Path(id): Path<i32>) -> StatusCode {
+    match state.database.remove_ignore_alert_term(id) {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(e) => {
+            error!("Failed to remove ignore alert term {id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub async fn list_ignore_alert_terms_handler(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    state
+        .database
+        .list_ignore_alert_terms()
+        .map(|terms| Json(serde_json::json!(terms.iter().map(|t| &t.term).collect::<Vec<_>>())))
+        .map_err(|e| {
+            error!("Failed to list ignore alert terms: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
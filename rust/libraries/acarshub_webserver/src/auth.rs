@@ -0,0 +1,190 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JWT-based auth: a login endpoint that issues tokens, and middleware that
+//! gates mutating requests behind a valid, unrevoked token while optionally
+//! letting reads (GET/HEAD) through anonymously.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Request, State};
+use axum::http::{Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Extension};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Per-deployment JWT signing/expiry configuration, set from
+/// `acarshub-settings`' `--jwt-secret`/`--jwt-token-ttl-seconds`/
+/// `--allow-anonymous-read` flags.
+#[derive(Clone)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+    pub token_ttl_seconds: i64,
+    pub allow_anonymous_read: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// User id.
+    sub: i32,
+    /// Unique id for this token, checked against `api_tokens` on every request.
+    jti: String,
+    iat: usize,
+    exp: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    token: String,
+    expires_in: i64,
+}
+
+/// The authenticated user attached to a request by [`require_auth`], available
+/// to handlers via `Extension<AuthUser>`.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub jti: String,
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+/// `POST /auth/login`: verifies `username`/`password` and, on success,
+/// issues a signed JWT recorded in `api_tokens` so it can be checked or
+/// revoked later.
+pub async fn login_handler(
+    State(state): State<AppState>,
+    Json(req): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, StatusCode> {
+    let user = state
+        .database
+        .verify_credentials(&req.username, &req.password)
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    let issued_at = now();
+    let expires_at = issued_at + state.auth_config.token_ttl_seconds;
+    let jti = Uuid::new_v4().to_string();
+
+    let claims = Claims {
+        sub: user.id,
+        jti: jti.clone(),
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        iat: issued_at as usize,
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        exp: expires_at as usize,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(state.auth_config.jwt_secret.as_bytes()),
+    )
+    .map_err(|e| {
+        error!("Failed to sign JWT: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    state
+        .database
+        .record_issued_token(user.id, &jti, issued_at, expires_at)
+        .map_err(|e| {
+            error!("Failed to record issued token: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(LoginResponse {
+        token,
+        expires_in: state.auth_config.token_ttl_seconds,
+    }))
+}
+
+/// `POST /auth/logout`: revokes the token the caller authenticated with, so
+/// it's rejected on its next use even though its `exp` claim hasn't passed.
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Result<StatusCode, StatusCode> {
+    state
+        .database
+        .revoke_token(&user.jti, now())
+        .map_err(|e| {
+            error!("Failed to revoke token: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    debug!("User {} logged out, revoked token {}", user.user_id, user.jti);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Middleware layered over the protected routes (everything except `/` and
+/// `/auth/login`): lets GET/HEAD through anonymously
+/// when `allow_anonymous_read` is set, otherwise requires (and validates) a
+/// `Bearer` JWT whose `jti` is still recorded and unrevoked in `api_tokens`.
+pub async fn require_auth(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD);
+    if is_read && state.auth_config.allow_anonymous_read {
+        return next.run(request).await;
+    }
+
+    match authenticate(&state, &request) {
+        Ok(user) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        Err(status) => status.into_response(),
+    }
+}
+
+fn authenticate(state: &AppState, request: &Request) -> Result<AuthUser, StatusCode> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(state.auth_config.jwt_secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    state
+        .database
+        .check_token_valid(&data.claims.jti, now())
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    Ok(AuthUser {
+        user_id: data.claims.sub,
+        jti: data.claims.jti,
+    })
+}
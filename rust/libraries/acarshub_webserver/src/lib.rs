@@ -29,28 +29,90 @@
 #[macro_use]
 extern crate tracing;
 
+mod api;
+mod auth;
+mod stream;
+
 use std::sync::Arc;
 
 use acarshub_database::AcarsHubDatabase;
+use acarshub_common::FoundMessage;
 use anyhow::Result;
-use axum::{Router, routing::get};
-use parking_lot::FairMutex;
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post},
+};
 use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+pub use auth::AuthConfig;
+pub use stream::StreamFilter;
+
+#[derive(Clone)]
+struct AppState {
+    database: Arc<AcarsHubDatabase>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    auth_config: Arc<AuthConfig>,
+}
 
 pub struct AcarsHubWebServer {
-    database: Arc<FairMutex<AcarsHubDatabase>>,
+    database: Arc<AcarsHubDatabase>,
+    message_tx: broadcast::Sender<FoundMessage>,
+    auth_config: AuthConfig,
 }
 
 impl AcarsHubWebServer {
-    /// Create a new instance of the web server
-    pub const fn new(database: Arc<FairMutex<AcarsHubDatabase>>) -> Self {
-        Self { database }
+    /// Create a new instance of the web server.
+    ///
+    /// `message_tx` is the broadcast sender that the message-processing
+    /// pipeline publishes every decoded `FoundMessage` onto; the webserver
+    /// subscribes to it once per streaming client so the UI can get a live
+    /// feed without polling the database. `auth_config` controls JWT
+    /// signing/expiry and whether reads are allowed without a token.
+    pub const fn new(
+        database: Arc<AcarsHubDatabase>,
+        message_tx: broadcast::Sender<FoundMessage>,
+        auth_config: AuthConfig,
+    ) -> Self {
+        Self {
+            database,
+            message_tx,
+            auth_config,
+        }
     }
 
     pub async fn run(&mut self) -> Result<()> {
         info!("Starting web server...");
-        // build our application with a single route
-        let app = Router::new().route("/", get(|| async { "Hello, World!" }));
+
+        let state = AppState {
+            database: self.database.clone(),
+            message_tx: self.message_tx.clone(),
+            auth_config: Arc::new(self.auth_config.clone()),
+        };
+
+        // Mutating endpoints: saving a message and maintaining the alert
+        // ignore list all require a valid, unrevoked token. The live message
+        // stream is included here too -- it's a read, so require_auth still
+        // lets it through anonymously when --allow-anonymous-read is set,
+        // but it's the most sensitive read in the app (every decoded
+        // message, unfiltered) so it must not bypass that check entirely.
+        let protected = Router::new()
+            .route("/auth/logout", post(auth::logout_handler))
+            .route("/api/messages/{id}/save", post(api::save_message_handler))
+            .route(
+                "/api/alert-terms",
+                post(api::add_ignore_alert_term_handler).get(api::list_ignore_alert_terms_handler),
+            )
+            .route("/api/alert-terms/{id}", delete(api::remove_ignore_alert_term_handler))
+            .route("/stream/sse", get(stream::sse_handler))
+            .route("/stream/ws", get(stream::ws_handler))
+            .layer(middleware::from_fn_with_state(state.clone(), auth::require_auth));
+
+        let app = Router::new()
+            .route("/", get(|| async { "Hello, World!" }))
+            .route("/auth/login", post(auth::login_handler))
+            .merge(protected)
+            .with_state(state);
 
         // run our app with hyper, listening globally on port 3000
         let listener = TcpListener::bind("0.0.0.0:3000").await?;
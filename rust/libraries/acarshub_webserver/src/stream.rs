@@ -0,0 +1,153 @@
+// Copyright (C) 2022-2025 Frederick Clausen II
+// This file is part of acarshub <https://github.com/sdr-enthusiasts/docker-acarshub>.
+
+// acarshub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// acarshub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with acarshub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Live message streaming: a Server-Sent-Events endpoint and a WebSocket
+//! endpoint that both forward the same `FoundMessage` broadcast feed.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use acars_vdlm2_parser::AcarsVdlm2Message;
+use acarshub_common::{FoundMessage, IngestedMessage, Protocols};
+use axum::{
+    extract::{Query, State, WebSocketUpgrade, ws::Message},
+    response::{
+        Sse,
+        sse::{Event, KeepAlive},
+    },
+};
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+
+use crate::AppState;
+
+/// Query-string filters accepted by both streaming endpoints, e.g.
+/// `/stream/sse?protocol=ACARS&tail=N12345`.
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct StreamFilter {
+    pub protocol: Option<String>,
+    pub tail: Option<String>,
+    pub flight: Option<String>,
+    pub freq: Option<String>,
+}
+
+impl StreamFilter {
+    fn matches(&self, found: &FoundMessage) -> bool {
+        if let Some(protocol) = &self.protocol {
+            if !protocol_matches(found.protocol, protocol) {
+                return false;
+            }
+        }
+
+        let IngestedMessage::Acars(AcarsVdlm2Message::AcarsMessage(msg)) = &found.message else {
+            // Tail/flight/freq filters are only evaluated against plain
+            // ACARS messages; every other variant (VDLM2/HFDL/Inmarsat/
+            // Iridium/ADS-B) is excluded whenever one of them is set.
+            return self.tail.is_none() && self.flight.is_none() && self.freq.is_none();
+        };
+
+        if let Some(tail) = &self.tail {
+            if msg.tail.as_deref() != Some(tail.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(flight) = &self.flight {
+            if msg.flight.as_deref() != Some(flight.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(freq) = &self.freq {
+            if &msg.freq.to_string() != freq {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn protocol_matches(protocol: Protocols, wanted: &str) -> bool {
+    protocol.to_string().eq_ignore_ascii_case(wanted)
+}
+
+fn filtered_event(filter: &StreamFilter, item: Result<FoundMessage, BroadcastStreamRecvError>) -> Option<Event> {
+    match item {
+        Ok(found) if filter.matches(&found) => {
+            serde_json::to_string(&found).ok().map(|json| Event::default().data(json))
+        }
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(dropped)) => {
+            warn!("Streaming client lagged, dropped {dropped} messages");
+            Some(Event::default().comment(format!("dropped {dropped} messages")))
+        }
+    }
+}
+
+pub async fn sse_handler(
+    State(state): State<AppState>,
+    Query(filter): Query<StreamFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.message_tx.subscribe();
+
+    let stream = BroadcastStream::new(rx).filter_map(move |item| {
+        let event = filtered_event(&filter, item);
+        async move { event.map(Ok) }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(filter): Query<StreamFilter>,
+) -> axum::response::Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state, filter))
+}
+
+async fn handle_socket(mut socket: axum::extract::ws::WebSocket, state: AppState, filter: StreamFilter) {
+    let mut rx = state.message_tx.subscribe();
+
+    loop {
+        let payload = match rx.recv().await {
+            Ok(found) => {
+                if !filter.matches(&found) {
+                    continue;
+                }
+                match serde_json::to_string(&found) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        error!("Failed to serialize streamed message: {e}");
+                        continue;
+                    }
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(dropped)) => {
+                warn!("WebSocket streaming client lagged, dropped {dropped} messages");
+                format!("{{\"dropped\":{dropped}}}")
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        if socket.send(Message::Text(payload.into())).await.is_err() {
+            // the client disconnected
+            break;
+        }
+    }
+}